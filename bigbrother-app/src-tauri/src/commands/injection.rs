@@ -0,0 +1,74 @@
+use crate::database::{get_connection, models::{InjectionEvent, InjectionEventKind}};
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+/// Store the prompt-injection detections of a verification
+pub fn save_injection_events(conn: &Connection, verification_id: i64, events: &[InjectionEvent]) -> Result<(), String> {
+    for event in events {
+        conn.execute(
+            "INSERT INTO injection_events (verification_id, kind, offset_seconds, detail) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![verification_id, event.kind.as_str(), event.offset_seconds, event.detail],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn load_injection_events(conn: &Connection, verification_id: i64) -> Result<Vec<InjectionEvent>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, offset_seconds, detail FROM injection_events
+             WHERE verification_id = ?1
+             ORDER BY id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([verification_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<f64>>(1)?, row.get::<_, String>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(kind, offset_seconds, detail)| {
+            InjectionEventKind::parse(&kind).map(|kind| InjectionEvent::new(kind, offset_seconds, detail))
+        })
+        .collect())
+}
+
+/// Possible prompt injections noticed while producing a verification, in the order they were found
+#[tauri::command]
+pub async fn get_injection_events(app: AppHandle, verification_id: i64) -> Result<Vec<InjectionEvent>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    load_injection_events(&conn, verification_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_events_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, title, due_date, min_duration) VALUES (1, 'Essay', '2024-10-17', 1800);
+             INSERT INTO task_verifications (id, task_id, verified) VALUES (1, 1, 0), (2, 1, 1);",
+        )
+        .unwrap();
+
+        let events = vec![
+            InjectionEvent::new(InjectionEventKind::OnScreenInstructions, Some(250.0), "Ignore previous instructions".to_string()),
+            InjectionEvent::new(InjectionEventKind::CanaryLeaked, None, "The answer contains the check token".to_string()),
+        ];
+        save_injection_events(&conn, 1, &events).unwrap();
+
+        assert_eq!(load_injection_events(&conn, 1).unwrap(), events);
+        assert!(load_injection_events(&conn, 2).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,485 @@
+use crate::commands::verification::{run_verification, VerifyError};
+use crate::database::{get_connection, models::{JobState, VerificationJob, VerificationOutcome}};
+use crate::verification::TimeWindow;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// Emitted whenever a job changes state
+pub const JOB_EVENT: &str = "verification-job";
+
+/// A job interrupted this many times (e.g. by quitting the app) is failed instead of resumed
+const MAX_JOB_ATTEMPTS: i64 = 3;
+
+type JobOutcome = Result<VerificationOutcome, VerifyError>;
+
+/// An appeal against an earlier verdict, carried by the job that re-verifies it
+#[derive(Debug, Clone)]
+pub struct JobAppeal {
+    pub parent_verification_id: i64,
+    pub justification: String,
+}
+
+/// Queue feeding the single verification worker. Jobs run one at a time so concurrent
+/// verifications can't race each other on spend or on the rate limit.
+pub struct VerificationQueue {
+    sender: mpsc::UnboundedSender<i64>,
+    running: Mutex<Option<(i64, tokio::task::AbortHandle)>>,
+    waiters: Mutex<HashMap<i64, Vec<oneshot::Sender<JobOutcome>>>>,
+}
+
+impl VerificationQueue {
+    /// Start the worker and return the queue that feeds it
+    pub fn start(app: AppHandle) -> Arc<Self> {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let queue = Arc::new(VerificationQueue {
+            sender,
+            running: Mutex::new(None),
+            waiters: Mutex::new(HashMap::new()),
+        });
+
+        let worker_queue = queue.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(job_id) = receiver.recv().await {
+                run_job(&app, &worker_queue, job_id).await;
+            }
+        });
+
+        queue
+    }
+
+    fn enqueue(&self, job_id: i64) -> Result<(), String> {
+        self.sender
+            .send(job_id)
+            .map_err(|_| "Verification worker is not running".to_string())
+    }
+
+    fn wait_for(&self, job_id: i64) -> oneshot::Receiver<JobOutcome> {
+        let (sender, receiver) = oneshot::channel();
+        self.waiters.lock().unwrap().entry(job_id).or_default().push(sender);
+        receiver
+    }
+
+    fn notify(&self, job_id: i64, outcome: &JobOutcome) {
+        for waiter in self.waiters.lock().unwrap().remove(&job_id).unwrap_or_default() {
+            let _ = waiter.send(outcome.clone());
+        }
+    }
+}
+
+/// Stage updates for one running job: persisted and sent to the UI
+pub struct JobReporter {
+    app: AppHandle,
+    job_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobEvent {
+    pub job: VerificationJob,
+    pub window: Option<TimeWindow>, // segment being worked on, for chunked verifications
+}
+
+impl JobReporter {
+    pub fn stage(&self, state: JobState, window: Option<&TimeWindow>) {
+        if let Err(e) = set_job_state(&self.app, self.job_id, state, None, None, window) {
+            eprintln!("Failed to update verification job {}: {}", self.job_id, e);
+        }
+    }
+}
+
+/// Persist a state change and emit it
+fn set_job_state(
+    app: &AppHandle,
+    job_id: i64,
+    state: JobState,
+    error: Option<&str>,
+    verification_id: Option<i64>,
+    window: Option<&TimeWindow>,
+) -> Result<(), String> {
+    let conn = get_connection(app).map_err(|e| e.to_string())?;
+    update_job(&conn, job_id, state, error, verification_id)?;
+    emit_job(app, &conn, job_id, window)
+}
+
+fn emit_job(app: &AppHandle, conn: &Connection, job_id: i64, window: Option<&TimeWindow>) -> Result<(), String> {
+    let job = load_job(conn, job_id)?.ok_or("Verification job disappeared")?;
+
+    app.emit(JOB_EVENT, JobEvent { job, window: window.copied() })
+        .map_err(|e| e.to_string())
+}
+
+/// Claim a queued job and start it. The claim and the spawn happen under the `running` lock, so
+/// `cancel_verification` sees the job either still queued or running, never in between.
+fn start_job(app: &AppHandle, queue: &VerificationQueue, job_id: i64) -> Result<Option<JoinHandle<JobOutcome>>, String> {
+    let mut running = queue.running.lock().unwrap();
+
+    let conn = get_connection(app).map_err(|e| e.to_string())?;
+    if !start_attempt(&conn, job_id)? {
+        return Ok(None); // cancelled while waiting
+    }
+    let job = load_job(&conn, job_id)?.ok_or("Verification job disappeared")?;
+
+    let reporter = JobReporter { app: app.clone(), job_id };
+    let task_app = app.clone();
+    let handle = tokio::spawn(async move {
+        run_verification(&task_app, &job, &reporter).await
+    });
+    *running = Some((job_id, handle.abort_handle()));
+
+    Ok(Some(handle))
+}
+
+async fn run_job(app: &AppHandle, queue: &VerificationQueue, job_id: i64) {
+    let handle = match start_job(app, queue, job_id) {
+        Ok(Some(handle)) => handle,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("Failed to start verification job {}: {}", job_id, e);
+            return;
+        }
+    };
+
+    let outcome = match handle.await {
+        Ok(outcome) => outcome,
+        Err(e) if e.is_cancelled() => Err(VerifyError::from("Verification was cancelled")),
+        Err(e) => Err(VerifyError::from(format!("Verification crashed: {}", e))),
+    };
+    *queue.running.lock().unwrap() = None;
+
+    let update = match &outcome {
+        Ok(done) => set_job_state(app, job_id, JobState::Done, None, Some(done.verification_id), None),
+        Err(e) => set_job_state(app, job_id, JobState::Failed, Some(e.message()), None, None),
+    };
+    if let Err(e) = update {
+        eprintln!("Failed to finish verification job {}: {}", job_id, e);
+    }
+
+    queue.notify(job_id, &outcome);
+}
+
+fn insert_job(
+    conn: &Connection,
+    task_id: i64,
+    override_budget: bool,
+    appeal: Option<&JobAppeal>,
+) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO verification_jobs (task_id, state, override_budget, parent_verification_id, appeal)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            task_id,
+            JobState::Queued.as_str(),
+            override_budget,
+            appeal.map(|a| a.parent_verification_id),
+            appeal.map(|a| a.justification.as_str())
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+fn update_job(
+    conn: &Connection,
+    job_id: i64,
+    state: JobState,
+    error: Option<&str>,
+    verification_id: Option<i64>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE verification_jobs
+         SET state = ?1, error = COALESCE(?2, error), verification_id = COALESCE(?3, verification_id),
+             updated_at = datetime('now')
+         WHERE id = ?4",
+        rusqlite::params![state.as_str(), error, verification_id, job_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Move a queued job to its first stage. Returns false if it is no longer queued.
+fn start_attempt(conn: &Connection, job_id: i64) -> Result<bool, String> {
+    let claimed = conn
+        .execute(
+            "UPDATE verification_jobs SET state = ?1, attempts = attempts + 1, updated_at = datetime('now')
+             WHERE id = ?2 AND state = ?3",
+            rusqlite::params![JobState::Extracting.as_str(), job_id, JobState::Queued.as_str()],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(claimed > 0)
+}
+
+/// Fail a job that has not started yet. Returns false if it is no longer queued.
+fn cancel_queued(conn: &Connection, job_id: i64) -> Result<bool, String> {
+    let cancelled = conn
+        .execute(
+            "UPDATE verification_jobs SET state = ?1, error = 'Verification was cancelled', updated_at = datetime('now')
+             WHERE id = ?2 AND state = ?3",
+            rusqlite::params![JobState::Failed.as_str(), job_id, JobState::Queued.as_str()],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(cancelled > 0)
+}
+
+const JOB_COLUMNS: &str =
+    "id, task_id, state, override_budget, attempts, error, verification_id, parent_verification_id, appeal, created_at, updated_at";
+
+fn job_from_row(row: &rusqlite::Row) -> rusqlite::Result<VerificationJob> {
+    let state: String = row.get(2)?;
+
+    Ok(VerificationJob {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        state: JobState::parse(&state).unwrap_or(JobState::Failed),
+        override_budget: row.get(3)?,
+        attempts: row.get(4)?,
+        error: row.get(5)?,
+        verification_id: row.get(6)?,
+        parent_verification_id: row.get(7)?,
+        appeal: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+fn load_job(conn: &Connection, job_id: i64) -> Result<Option<VerificationJob>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM verification_jobs WHERE id = ?1", JOB_COLUMNS),
+        [job_id],
+        job_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Jobs left unfinished by the previous run go back in the queue, unless they have already been
+/// interrupted too often. Chunked verifications pick up from their saved segments.
+/// Returns the ids to enqueue, oldest first.
+fn recover_jobs(conn: &Connection) -> Result<Vec<i64>, String> {
+    conn.execute(
+        "UPDATE verification_jobs
+         SET state = ?1, error = 'Interrupted too many times', updated_at = datetime('now')
+         WHERE state NOT IN (?1, ?2) AND attempts >= ?3",
+        rusqlite::params![JobState::Failed.as_str(), JobState::Done.as_str(), MAX_JOB_ATTEMPTS],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE verification_jobs SET state = ?1, updated_at = datetime('now') WHERE state NOT IN (?2, ?3)",
+        rusqlite::params![JobState::Queued.as_str(), JobState::Failed.as_str(), JobState::Done.as_str()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT id FROM verification_jobs WHERE state = ?1 ORDER BY id")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([JobState::Queued.as_str()], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| e.to_string());
+
+    ids
+}
+
+/// Called once at startup, after the worker is running
+pub fn resume_unfinished_jobs(app: &AppHandle, queue: &VerificationQueue) -> Result<(), String> {
+    let conn = get_connection(app).map_err(|e| e.to_string())?;
+
+    for job_id in recover_jobs(&conn)? {
+        println!("Resuming verification job {}", job_id);
+        queue.enqueue(job_id)?;
+    }
+
+    Ok(())
+}
+
+/// Create a queued job; the caller hands it to the worker
+fn create_job(
+    app: &AppHandle,
+    task_id: i64,
+    override_budget: bool,
+    appeal: Option<&JobAppeal>,
+) -> Result<VerificationJob, String> {
+    let conn = get_connection(app).map_err(|e| e.to_string())?;
+    let job_id = insert_job(&conn, task_id, override_budget, appeal)?;
+    let job = load_job(&conn, job_id)?.ok_or("Failed to create verification job")?;
+
+    if let Err(e) = app.emit(JOB_EVENT, JobEvent { job: job.clone(), window: None }) {
+        eprintln!("Failed to emit verification job event: {}", e);
+    }
+
+    Ok(job)
+}
+
+/// Queue a verification and return immediately; progress arrives as `verification-job` events
+#[tauri::command]
+pub async fn queue_verification(
+    app: AppHandle,
+    queue: State<'_, Arc<VerificationQueue>>,
+    task_id: i64,
+    override_budget: Option<bool>,
+) -> Result<VerificationJob, String> {
+    let job = create_job(&app, task_id, override_budget.unwrap_or(false), None)?;
+    queue.enqueue(job.id)?;
+    Ok(job)
+}
+
+/// Queue a verification, or a re-verification for an appeal, and wait for its result
+pub async fn submit_and_wait(
+    app: &AppHandle,
+    queue: &VerificationQueue,
+    task_id: i64,
+    override_budget: bool,
+    appeal: Option<&JobAppeal>,
+) -> JobOutcome {
+    let job = create_job(app, task_id, override_budget, appeal)?;
+    let receiver = queue.wait_for(job.id);
+    queue.enqueue(job.id)?;
+
+    receiver
+        .await
+        .unwrap_or_else(|_| Err(VerifyError::from("Verification worker stopped")))
+}
+
+#[tauri::command]
+pub async fn cancel_verification(
+    app: AppHandle,
+    queue: State<'_, Arc<VerificationQueue>>,
+    job_id: i64,
+) -> Result<(), String> {
+    // Held throughout so the worker can't claim the job between the two checks
+    let running = queue.running.lock().unwrap();
+
+    // A running job is aborted; the worker marks it failed once it has stopped. One that finished
+    // but hasn't been cleared yet is stored as done, so it can't be cancelled any more.
+    if let Some((running_id, handle)) = running.as_ref() {
+        if *running_id == job_id {
+            if handle.is_finished() {
+                return Err("Verification job has already finished".to_string());
+            }
+            handle.abort();
+            return Ok(());
+        }
+    }
+
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    if cancel_queued(&conn, job_id)? {
+        drop(running);
+        emit_job(&app, &conn, job_id, None)?;
+        queue.notify(job_id, &Err(VerifyError::from("Verification was cancelled")));
+        return Ok(());
+    }
+
+    match load_job(&conn, job_id)? {
+        Some(_) => Err("Verification job has already finished".to_string()),
+        None => Err("Verification job not found".to_string()),
+    }
+}
+
+#[tauri::command]
+pub async fn list_verification_jobs(
+    app: AppHandle,
+    task_id: Option<i64>,
+) -> Result<Vec<VerificationJob>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM verification_jobs
+             WHERE ?1 IS NULL OR task_id = ?1
+             ORDER BY id DESC
+             LIMIT 100",
+            JOB_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let jobs = stmt
+        .query_map([task_id], job_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+
+    jobs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn conn_with_task() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO tasks (id, title, due_date, min_duration) VALUES (1, 'Essay', '2024-10-17', 30)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_job_state_round_trip() {
+        let conn = conn_with_task();
+        let job_id = insert_job(&conn, 1, true, None).unwrap();
+
+        update_job(&conn, job_id, JobState::Uploading, None, None).unwrap();
+        let job = load_job(&conn, job_id).unwrap().unwrap();
+        assert_eq!(job.state, JobState::Uploading);
+        assert!(job.override_budget);
+
+        update_job(&conn, job_id, JobState::Failed, Some("boom"), None).unwrap();
+        assert_eq!(load_job(&conn, job_id).unwrap().unwrap().error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_recover_requeues_interrupted_jobs() {
+        let conn = conn_with_task();
+        let queued = insert_job(&conn, 1, false, None).unwrap();
+        let interrupted = insert_job(&conn, 1, false, None).unwrap();
+        let worn_out = insert_job(&conn, 1, false, None).unwrap();
+        let done = insert_job(&conn, 1, false, None).unwrap();
+
+        assert!(start_attempt(&conn, interrupted).unwrap());
+        for _ in 0..MAX_JOB_ATTEMPTS {
+            update_job(&conn, worn_out, JobState::Queued, None, None).unwrap();
+            assert!(start_attempt(&conn, worn_out).unwrap());
+        }
+        update_job(&conn, worn_out, JobState::Uploading, None, None).unwrap();
+        update_job(&conn, done, JobState::Done, None, None).unwrap();
+
+        assert_eq!(recover_jobs(&conn).unwrap(), vec![queued, interrupted]);
+        assert_eq!(load_job(&conn, worn_out).unwrap().unwrap().state, JobState::Failed);
+        assert_eq!(load_job(&conn, done).unwrap().unwrap().state, JobState::Done);
+    }
+
+    #[test]
+    fn test_job_cancelled_before_claim_never_starts() {
+        let conn = conn_with_task();
+        let job_id = insert_job(&conn, 1, false, None).unwrap();
+
+        // The worker has loaded the job as queued when the cancel lands
+        assert_eq!(load_job(&conn, job_id).unwrap().unwrap().state, JobState::Queued);
+        assert!(cancel_queued(&conn, job_id).unwrap());
+
+        assert!(!start_attempt(&conn, job_id).unwrap());
+        let job = load_job(&conn, job_id).unwrap().unwrap();
+        assert_eq!(job.state, JobState::Failed);
+        assert_eq!(job.attempts, 0);
+        assert_eq!(job.error.as_deref(), Some("Verification was cancelled"));
+
+        // ...and a claimed job can no longer be cancelled as queued
+        let started = insert_job(&conn, 1, false, None).unwrap();
+        assert!(start_attempt(&conn, started).unwrap());
+        assert!(!cancel_queued(&conn, started).unwrap());
+        assert_eq!(load_job(&conn, started).unwrap().unwrap().state, JobState::Extracting);
+    }
+}
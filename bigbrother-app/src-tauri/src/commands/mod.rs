@@ -1,5 +1,10 @@
 pub mod tasks;
 pub mod recording;
 pub mod verification;
+pub mod jobs;
 pub mod settings;
+pub mod templates;
+pub mod timeline;
+pub mod injection;
+pub mod usage;
 pub mod utils;
@@ -102,6 +102,9 @@ pub async fn start_recording(
         end_time: None,
         file_path: file_path.clone(),
         status: "recording".to_string(),
+        webcam_region: None,
+        activity_profile: None,
+        fingerprint: None,
     });
 
     println!("Started recording for task {}", task_id);
@@ -212,12 +215,14 @@ pub async fn stop_recording(
         }
     }
 
+    let mut webcam_index = None;
     let webcam_file = format!("{}_webcam.mp4", output_base);
     let webcam_path = std::path::Path::new(&webcam_file);
     if webcam_path.exists() {
         if let Ok(metadata) = std::fs::metadata(&webcam_file) {
             if metadata.len() > 0 {
                 println!("Found webcam file: {} ({} bytes)", webcam_file, metadata.len());
+                webcam_index = Some(temp_files.len());
                 temp_files.push(webcam_file);
             } else {
                 println!("Warning: Webcam file is empty, skipping");
@@ -231,6 +236,7 @@ pub async fn stop_recording(
     }
 
     // Combine videos if any were recorded
+    let mut webcam_region = None;
     if !temp_files.is_empty() {
         println!("Combining {} videos into grid layout...", temp_files.len());
         println!("Output file will be: {}", output_path);
@@ -239,6 +245,7 @@ pub async fn stop_recording(
         match combiner.combine_grid() {
             Ok(_) => {
                 println!("Videos combined successfully");
+                webcam_region = webcam_index.and_then(|idx| combiner.grid_cell(idx));
                 // Clean up temporary files only if combine succeeded
                 println!("Cleaning up temporary files...");
                 for temp_file in &temp_files {
@@ -269,11 +276,16 @@ pub async fn stop_recording(
 
     let current = state.current_recording.lock().unwrap();
     if let Some(recording) = current.as_ref() {
+        let webcam_region = webcam_region
+            .map(|region| serde_json::to_string(&region))
+            .transpose()
+            .map_err(|e| e.to_string())?;
+
         conn.execute(
             "UPDATE recordings
-             SET duration = ?1, end_time = ?2, status = 'completed'
-             WHERE id = ?3",
-            rusqlite::params![final_duration, end_time, recording.id],
+             SET duration = ?1, end_time = ?2, status = 'completed', webcam_region = ?3
+             WHERE id = ?4",
+            rusqlite::params![final_duration, end_time, webcam_region, recording.id],
         )
         .map_err(|e| e.to_string())?;
 
@@ -1,6 +1,41 @@
-use crate::database::get_connection;
+use crate::database::{get_connection, models::VerificationSettings};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
 use tauri::AppHandle;
 
+const VERIFICATION_SETTINGS_KEY: &str = "verification";
+
+/// Read a JSON setting, falling back to the default when it hasn't been saved yet
+pub fn load_setting<T: DeserializeOwned + Default>(conn: &Connection, key: &str) -> Result<T, String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match value {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid value for setting '{}': {}", key, e)),
+        None => Ok(T::default()),
+    }
+}
+
+pub fn save_setting<T: Serialize>(conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        rusqlite::params![key, json],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+pub fn load_verification_settings(conn: &Connection) -> Result<VerificationSettings, String> {
+    load_setting(conn, VERIFICATION_SETTINGS_KEY)
+}
+
 #[tauri::command]
 pub async fn set_claude_api_key(app: AppHandle, api_key: String) -> Result<(), String> {
     let conn = get_connection(&app).map_err(|e| e.to_string())?;
@@ -26,3 +61,19 @@ pub async fn get_claude_api_key(app: AppHandle) -> Result<Option<String>, String
 
     Ok(api_key)
 }
+
+#[tauri::command]
+pub async fn get_verification_settings(app: AppHandle) -> Result<VerificationSettings, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    load_verification_settings(&conn)
+}
+
+#[tauri::command]
+pub async fn update_verification_settings(
+    app: AppHandle,
+    settings: VerificationSettings,
+) -> Result<VerificationSettings, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    save_setting(&conn, VERIFICATION_SETTINGS_KEY, &settings)?;
+    Ok(settings)
+}
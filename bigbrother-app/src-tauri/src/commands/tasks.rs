@@ -1,6 +1,57 @@
 use crate::database::{get_connection, models::Task};
+use crate::verification::parser::same_criterion;
+use rusqlite::Connection;
 use tauri::AppHandle;
 
+/// Replace a task's success criteria, dropping blank ones and repeats. Scores are matched to
+/// criteria by their text, so two criteria that only differ in case or punctuation would be
+/// indistinguishable.
+pub fn save_criteria(conn: &Connection, task_id: i64, criteria: &[String]) -> Result<(), String> {
+    conn.execute("DELETE FROM task_criteria WHERE task_id = ?1", [task_id])
+        .map_err(|e| e.to_string())?;
+
+    let mut kept: Vec<&str> = Vec::new();
+    for criterion in criteria.iter().map(|c| c.trim()).filter(|c| !c.is_empty()) {
+        if !kept.iter().any(|earlier| same_criterion(earlier, criterion)) {
+            kept.push(criterion);
+        }
+    }
+
+    for (position, description) in kept.into_iter().enumerate() {
+        conn.execute(
+            "INSERT INTO task_criteria (task_id, position, description) VALUES (?1, ?2, ?3)",
+            rusqlite::params![task_id, position as i64, description],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// A task's success criteria, in order
+pub fn load_criteria(conn: &Connection, task_id: i64) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT description FROM task_criteria WHERE task_id = ?1 ORDER BY position ASC, id ASC")
+        .map_err(|e| e.to_string())?;
+
+    let criteria = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(criteria)
+}
+
+fn with_criteria(conn: &Connection, mut tasks: Vec<Task>) -> Result<Vec<Task>, String> {
+    for task in &mut tasks {
+        if let Some(id) = task.id {
+            task.criteria = load_criteria(conn, id)?;
+        }
+    }
+    Ok(tasks)
+}
+
 #[tauri::command]
 pub async fn create_task(
     app: AppHandle,
@@ -8,18 +59,21 @@ pub async fn create_task(
     description: Option<String>,
     due_date: String,
     min_duration: i64,
+    task_type: Option<String>,
+    criteria: Option<Vec<String>>,
 ) -> Result<Task, String> {
     let conn = get_connection(&app).map_err(|e| e.to_string())?;
 
     let result = conn.execute(
-        "INSERT INTO tasks (user_id, title, description, due_date, min_duration, status)
-         VALUES (1, ?1, ?2, ?3, ?4, 'pending')",
-        rusqlite::params![title, description, due_date, min_duration],
+        "INSERT INTO tasks (user_id, title, description, due_date, min_duration, status, task_type)
+         VALUES (1, ?1, ?2, ?3, ?4, 'pending', ?5)",
+        rusqlite::params![title, description, due_date, min_duration, task_type],
     );
 
     match result {
         Ok(_) => {
             let task_id = conn.last_insert_rowid();
+            save_criteria(&conn, task_id, &criteria.unwrap_or_default())?;
             get_task(app, task_id).await
         }
         Err(e) => Err(e.to_string()),
@@ -32,7 +86,7 @@ pub async fn get_all_tasks(app: AppHandle) -> Result<Vec<Task>, String> {
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at
+            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at, task_type
              FROM tasks
              ORDER BY due_date ASC",
         )
@@ -51,13 +105,15 @@ pub async fn get_all_tasks(app: AppHandle) -> Result<Vec<Task>, String> {
                 video_path: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                task_type: row.get(10)?,
+                criteria: Vec::new(),
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<Task>, _>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(tasks)
+    with_criteria(&conn, tasks)
 }
 
 #[tauri::command]
@@ -66,12 +122,12 @@ pub async fn get_task(app: AppHandle, id: i64) -> Result<Task, String> {
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at
+            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at, task_type
              FROM tasks WHERE id = ?1",
         )
         .map_err(|e| e.to_string())?;
 
-    let task = stmt
+    let mut task = stmt
         .query_row([id], |row| {
             Ok(Task {
                 id: Some(row.get(0)?),
@@ -84,9 +140,12 @@ pub async fn get_task(app: AppHandle, id: i64) -> Result<Task, String> {
                 video_path: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                task_type: row.get(10)?,
+                criteria: Vec::new(),
             })
         })
         .map_err(|e| e.to_string())?;
+    task.criteria = load_criteria(&conn, id)?;
 
     Ok(task)
 }
@@ -97,8 +156,8 @@ pub async fn update_task(app: AppHandle, id: i64, task: Task) -> Result<Task, St
 
     conn.execute(
         "UPDATE tasks
-         SET title = ?1, description = ?2, due_date = ?3, min_duration = ?4, status = ?5, video_path = ?6, updated_at = datetime('now', 'localtime')
-         WHERE id = ?7",
+         SET title = ?1, description = ?2, due_date = ?3, min_duration = ?4, status = ?5, video_path = ?6, task_type = ?7, updated_at = datetime('now', 'localtime')
+         WHERE id = ?8",
         rusqlite::params![
             task.title,
             task.description,
@@ -106,10 +165,12 @@ pub async fn update_task(app: AppHandle, id: i64, task: Task) -> Result<Task, St
             task.min_duration,
             task.status,
             task.video_path,
+            task.task_type,
             id
         ],
     )
     .map_err(|e| e.to_string())?;
+    save_criteria(&conn, id, &task.criteria)?;
 
     get_task(app, id).await
 }
@@ -118,6 +179,8 @@ pub async fn update_task(app: AppHandle, id: i64, task: Task) -> Result<Task, St
 pub async fn delete_task(app: AppHandle, id: i64) -> Result<(), String> {
     let conn = get_connection(&app).map_err(|e| e.to_string())?;
 
+    conn.execute("DELETE FROM task_criteria WHERE task_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
     conn.execute("DELETE FROM tasks WHERE id = ?1", [id])
         .map_err(|e| e.to_string())?;
 
@@ -130,7 +193,7 @@ pub async fn get_pending_tasks(app: AppHandle) -> Result<Vec<Task>, String> {
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at
+            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at, task_type
              FROM tasks
              WHERE status = 'pending'
              ORDER BY due_date ASC",
@@ -150,13 +213,15 @@ pub async fn get_pending_tasks(app: AppHandle) -> Result<Vec<Task>, String> {
                 video_path: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                task_type: row.get(10)?,
+                criteria: Vec::new(),
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<Task>, _>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(tasks)
+    with_criteria(&conn, tasks)
 }
 
 #[tauri::command]
@@ -165,9 +230,9 @@ pub async fn get_completed_tasks(app: AppHandle) -> Result<Vec<Task>, String> {
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at
+            "SELECT id, user_id, title, description, due_date, min_duration, status, video_path, created_at, updated_at, task_type
              FROM tasks
-             WHERE status IN ('completed', 'failed')
+             WHERE status IN ('completed', 'failed', 'needs_review')
              ORDER BY updated_at DESC",
         )
         .map_err(|e| e.to_string())?;
@@ -185,11 +250,40 @@ pub async fn get_completed_tasks(app: AppHandle) -> Result<Vec<Task>, String> {
                 video_path: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                task_type: row.get(10)?,
+                criteria: Vec::new(),
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<Task>, _>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(tasks)
+    with_criteria(&conn, tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_criteria_are_replaced_in_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute("INSERT INTO tasks (id, title, due_date, min_duration) VALUES (1, 'Calculus', '2024-10-17', 1800)", [])
+            .unwrap();
+
+        let criteria = vec![
+            "Solved at least 5 practice problems".to_string(),
+            "  ".to_string(),
+            " Checked answers ".to_string(),
+            "checked answers.".to_string(),
+        ];
+        save_criteria(&conn, 1, &criteria).unwrap();
+        assert_eq!(load_criteria(&conn, 1).unwrap(), vec!["Solved at least 5 practice problems", "Checked answers"]);
+
+        save_criteria(&conn, 1, &["IDE visible with tests passing".to_string()]).unwrap();
+        assert_eq!(load_criteria(&conn, 1).unwrap(), vec!["IDE visible with tests passing"]);
+        assert!(load_criteria(&conn, 2).unwrap().is_empty());
+    }
 }
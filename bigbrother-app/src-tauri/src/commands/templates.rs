@@ -0,0 +1,157 @@
+use crate::database::{get_connection, models::PromptTemplate};
+use crate::verification::template::{builtin_template, unknown_placeholders, PLACEHOLDERS, DEFAULT_TEMPLATE_NAME};
+use rusqlite::{Connection, OptionalExtension};
+use tauri::AppHandle;
+
+const TEMPLATE_COLUMNS: &str = "id, name, version, body, created_at";
+
+fn template_from_row(row: &rusqlite::Row) -> rusqlite::Result<PromptTemplate> {
+    Ok(PromptTemplate {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        version: row.get(2)?,
+        body: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// Newest stored version of a template
+fn latest_template(conn: &Connection, name: &str) -> Result<Option<PromptTemplate>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM prompt_templates WHERE name = ?1 ORDER BY version DESC LIMIT 1",
+            TEMPLATE_COLUMNS
+        ),
+        [name],
+        template_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// The template for a task: the newest version named after its type, else the newest
+/// stored "default", else the built-in template
+pub fn resolve_template(conn: &Connection, task_type: Option<&str>) -> Result<PromptTemplate, String> {
+    if let Some(task_type) = task_type.map(str::trim).filter(|t| !t.is_empty()) {
+        if let Some(template) = latest_template(conn, task_type)? {
+            return Ok(template);
+        }
+    }
+
+    Ok(latest_template(conn, DEFAULT_TEMPLATE_NAME)?.unwrap_or_else(builtin_template))
+}
+
+/// Store `body` as the next version of `name`
+fn insert_template(conn: &Connection, name: &str, body: &str) -> Result<PromptTemplate, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+
+    let unknown = unknown_placeholders(body);
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown placeholder(s): {}. Available: {}",
+            unknown.join(", "),
+            PLACEHOLDERS.join(", ")
+        ));
+    }
+
+    conn.execute(
+        "INSERT INTO prompt_templates (name, version, body)
+         VALUES (?1, (SELECT COALESCE(MAX(version), 0) + 1 FROM prompt_templates WHERE name = ?1), ?2)",
+        rusqlite::params![name, body],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        &format!("SELECT {} FROM prompt_templates WHERE id = ?1", TEMPLATE_COLUMNS),
+        [conn.last_insert_rowid()],
+        template_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Every stored template version, newest first within each name
+#[tauri::command]
+pub async fn list_prompt_templates(app: AppHandle) -> Result<Vec<PromptTemplate>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM prompt_templates ORDER BY name ASC, version DESC",
+            TEMPLATE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let templates = stmt
+        .query_map([], template_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(templates)
+}
+
+/// A specific version of a template, or the newest one. Version 0 of "default" is the built-in template.
+#[tauri::command]
+pub async fn get_prompt_template(
+    app: AppHandle,
+    name: String,
+    version: Option<i64>,
+) -> Result<Option<PromptTemplate>, String> {
+    let builtin = builtin_template();
+    if name == builtin.name && version == Some(builtin.version) {
+        return Ok(Some(builtin));
+    }
+
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+
+    match version {
+        Some(version) => conn
+            .query_row(
+                &format!("SELECT {} FROM prompt_templates WHERE name = ?1 AND version = ?2", TEMPLATE_COLUMNS),
+                rusqlite::params![name, version],
+                template_from_row,
+            )
+            .optional()
+            .map_err(|e| e.to_string()),
+        None => Ok(latest_template(&conn, &name)?.or_else(|| (name == builtin.name).then_some(builtin))),
+    }
+}
+
+/// Save a new version of a template. Earlier versions are kept so old verdicts stay traceable.
+#[tauri::command]
+pub async fn save_prompt_template(app: AppHandle, name: String, body: String) -> Result<PromptTemplate, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    insert_template(&conn, &name, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_versions_and_resolution() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+
+        assert_eq!(resolve_template(&conn, Some("coding")).unwrap(), builtin_template());
+
+        let first = insert_template(&conn, "coding", "Code review of {{title}}").unwrap();
+        let second = insert_template(&conn, "coding", "Coding session: {{title}}, {{required_minutes}} min").unwrap();
+        assert_eq!((first.version, second.version), (1, 2));
+
+        let resolved = resolve_template(&conn, Some("coding")).unwrap();
+        assert_eq!((resolved.name.as_str(), resolved.version), ("coding", 2));
+
+        insert_template(&conn, DEFAULT_TEMPLATE_NAME, "Task: {{title}}").unwrap();
+        let fallback = resolve_template(&conn, Some("reading")).unwrap();
+        assert_eq!((fallback.name.as_str(), fallback.version), ("default", 1));
+        assert_eq!(resolve_template(&conn, None).unwrap(), fallback);
+
+        assert!(insert_template(&conn, "exercise", "{{heart_rate}}").unwrap_err().contains("heart_rate"));
+        assert!(insert_template(&conn, "  ", "{{title}}").is_err());
+    }
+}
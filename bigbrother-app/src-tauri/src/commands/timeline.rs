@@ -0,0 +1,158 @@
+use crate::database::{get_connection, models::{ActivityCategory, TimelineEntry, TimelineTotal}};
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+/// Store the structured timeline of a verification
+pub fn save_timeline(conn: &Connection, verification_id: i64, entries: &[TimelineEntry]) -> Result<(), String> {
+    for entry in entries {
+        conn.execute(
+            "INSERT INTO verification_timeline (verification_id, start_seconds, end_seconds, category, activity, label)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                verification_id,
+                entry.start_seconds,
+                entry.end_seconds,
+                entry.category.as_str(),
+                entry.activity,
+                entry.label
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn load_timeline(conn: &Connection, verification_id: i64) -> Result<Vec<TimelineEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT start_seconds, end_seconds, category, activity, label
+             FROM verification_timeline
+             WHERE verification_id = ?1
+             ORDER BY start_seconds ASC, id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map([verification_id], |row| {
+            let category: String = row.get(2)?;
+            Ok(TimelineEntry {
+                start_seconds: row.get(0)?,
+                end_seconds: row.get(1)?,
+                category: ActivityCategory::parse(&category).unwrap_or_default(),
+                activity: row.get(3)?,
+                label: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+
+    entries
+}
+
+/// Time per category and label over the latest verification of each task, so appeals and
+/// re-verifications of the same recording aren't counted twice
+fn timeline_totals(conn: &Connection, task_id: Option<i64>, modifier: &str) -> Result<Vec<TimelineTotal>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.category, t.label, SUM(t.end_seconds - t.start_seconds), COUNT(DISTINCT t.verification_id)
+             FROM verification_timeline t
+             JOIN task_verifications v ON v.id = t.verification_id
+             WHERE v.id IN (SELECT MAX(id) FROM task_verifications GROUP BY task_id)
+               AND (?1 IS NULL OR v.task_id = ?1)
+               AND v.verified_at >= datetime('now', ?2)
+             GROUP BY t.category, t.label
+             ORDER BY SUM(t.end_seconds - t.start_seconds) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let totals = stmt
+        .query_map(rusqlite::params![task_id, modifier], |row| {
+            let category: String = row.get(0)?;
+            Ok(TimelineTotal {
+                category: ActivityCategory::parse(&category).unwrap_or_default(),
+                label: row.get(1)?,
+                total_seconds: row.get(2)?,
+                sessions: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+
+    totals
+}
+
+/// Timeline of one verification in recording order, for drawing under the video
+#[tauri::command]
+pub async fn get_verification_timeline(app: AppHandle, verification_id: i64) -> Result<Vec<TimelineEntry>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    load_timeline(&conn, verification_id)
+}
+
+/// Time spent per category and app/site over the last `days` days, largest first.
+/// Restrict to one task with `task_id`.
+#[tauri::command]
+pub async fn get_timeline_totals(
+    app: AppHandle,
+    days: Option<u32>,
+    task_id: Option<i64>,
+) -> Result<Vec<TimelineTotal>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    timeline_totals(&conn, task_id, &format!("-{} days", days.unwrap_or(30)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn entry(start: f64, end: f64, category: ActivityCategory, label: Option<&str>) -> TimelineEntry {
+        TimelineEntry {
+            start_seconds: start,
+            end_seconds: end,
+            category,
+            activity: String::new(),
+            label: label.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_totals_use_latest_verification_per_task() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, title, due_date, min_duration) VALUES (1, 'Essay', '2024-10-17', 1800),
+                                                                         (2, 'Reading', '2024-10-17', 1800);
+             INSERT INTO task_verifications (id, task_id, verified) VALUES (1, 1, 0), (2, 1, 1), (3, 2, 1);",
+        )
+        .unwrap();
+
+        // Verification 1 was superseded by 2 and must not count
+        save_timeline(&conn, 1, &[entry(0.0, 600.0, ActivityCategory::OffTask, Some("youtube.com"))]).unwrap();
+        save_timeline(
+            &conn,
+            2,
+            &[
+                entry(60.0, 120.0, ActivityCategory::OffTask, Some("youtube.com")),
+                entry(0.0, 60.0, ActivityCategory::OnTask, Some("Word")),
+            ],
+        )
+        .unwrap();
+        save_timeline(&conn, 3, &[entry(0.0, 90.0, ActivityCategory::OffTask, Some("youtube.com"))]).unwrap();
+
+        let timeline = load_timeline(&conn, 2).unwrap();
+        assert_eq!(timeline[0].category, ActivityCategory::OnTask);
+        assert_eq!(timeline[1].label.as_deref(), Some("youtube.com"));
+
+        let totals = timeline_totals(&conn, None, "-30 days").unwrap();
+        assert_eq!(totals[0].category, ActivityCategory::OffTask);
+        assert_eq!(totals[0].total_seconds, 150.0);
+        assert_eq!(totals[0].sessions, 2);
+
+        let essay = timeline_totals(&conn, Some(1), "-30 days").unwrap();
+        assert_eq!(essay.len(), 2);
+        assert_eq!(essay[0].total_seconds, 60.0);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::commands::settings::load_verification_settings;
+use crate::database::{get_connection, models::{BudgetStatus, SpendSummary}};
+use crate::verification::TokenUsage;
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+/// Store the billed calls of a verification attempt, returning the new row ids
+pub fn record_usage(conn: &Connection, task_id: i64, usage: &[TokenUsage]) -> Result<Vec<i64>, String> {
+    let mut ids = Vec::with_capacity(usage.len());
+
+    for call in usage {
+        conn.execute(
+            "INSERT INTO api_usage (task_id, model, input_tokens, output_tokens, cost_usd)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![task_id, call.model, call.input_tokens, call.output_tokens, call.cost_usd()],
+        )
+        .map_err(|e| e.to_string())?;
+
+        ids.push(conn.last_insert_rowid());
+    }
+
+    Ok(ids)
+}
+
+/// Attach usage rows to the verification they produced
+pub fn link_usage(conn: &Connection, usage_ids: &[i64], verification_id: i64) -> Result<(), String> {
+    for id in usage_ids {
+        conn.execute(
+            "UPDATE api_usage SET verification_id = ?1 WHERE id = ?2",
+            rusqlite::params![verification_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Group usage by a local-time `strftime` format, newest first
+fn spend_by_period(conn: &Connection, format: &str, modifier: &str) -> Result<Vec<SpendSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT strftime(?1, created_at, 'localtime') AS period, COUNT(*),
+                    SUM(input_tokens), SUM(output_tokens), SUM(cost_usd)
+             FROM api_usage
+             WHERE created_at >= datetime('now', ?2)
+             GROUP BY period
+             ORDER BY period DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![format, modifier], |row| {
+            Ok(SpendSummary {
+                period: row.get(0)?,
+                task_id: None,
+                requests: row.get(1)?,
+                input_tokens: row.get(2)?,
+                output_tokens: row.get(3)?,
+                cost_usd: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Total spend so far in the current calendar month
+pub fn month_to_date_spend(conn: &Connection) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(cost_usd), 0) FROM api_usage
+         WHERE strftime('%Y-%m', created_at, 'localtime') = strftime('%Y-%m', 'now', 'localtime')",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_budget_status(app: AppHandle) -> Result<BudgetStatus, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    let settings = load_verification_settings(&conn)?;
+    let month_spent_usd = month_to_date_spend(&conn)?;
+
+    Ok(BudgetStatus {
+        month_spent_usd,
+        monthly_budget_usd: settings.monthly_budget_usd,
+        remaining_usd: settings.monthly_budget_usd.map(|b| (b - month_spent_usd).max(0.0)),
+        max_verification_cost_usd: settings.max_verification_cost_usd,
+    })
+}
+
+#[tauri::command]
+pub async fn get_spend_by_day(app: AppHandle, days: Option<u32>) -> Result<Vec<SpendSummary>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    spend_by_period(&conn, "%Y-%m-%d", &format!("-{} days", days.unwrap_or(30)))
+}
+
+#[tauri::command]
+pub async fn get_spend_by_month(app: AppHandle, months: Option<u32>) -> Result<Vec<SpendSummary>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    spend_by_period(&conn, "%Y-%m", &format!("-{} months", months.unwrap_or(12)))
+}
+
+#[tauri::command]
+pub async fn get_spend_by_task(app: AppHandle) -> Result<Vec<SpendSummary>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(t.title, 'Deleted task'), u.task_id, COUNT(*),
+                    SUM(u.input_tokens), SUM(u.output_tokens), SUM(u.cost_usd)
+             FROM api_usage u
+             LEFT JOIN tasks t ON t.id = u.task_id
+             GROUP BY u.task_id
+             ORDER BY SUM(u.cost_usd) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SpendSummary {
+                period: row.get(0)?,
+                task_id: row.get(1)?,
+                requests: row.get(2)?,
+                input_tokens: row.get(3)?,
+                output_tokens: row.get(4)?,
+                cost_usd: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn test_records_and_groups_spend() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, title, due_date, min_duration) VALUES (7, 'Essay', '2024-10-17', 30);
+             INSERT INTO task_verifications (id, task_id, verified) VALUES (3, 7, 1);",
+        )
+        .unwrap();
+
+        let usage = vec![
+            TokenUsage { model: "claude-3-5-sonnet-20241022".to_string(), input_tokens: 1_000_000, output_tokens: 0 },
+            TokenUsage { model: "claude-3-5-sonnet-20241022".to_string(), input_tokens: 0, output_tokens: 100_000 },
+        ];
+        let ids = record_usage(&conn, 7, &usage).unwrap();
+        link_usage(&conn, &ids, 3).unwrap();
+
+        let days = spend_by_period(&conn, "%Y-%m-%d", "-30 days").unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].requests, 2);
+        assert!((days[0].cost_usd - 4.5).abs() < 1e-9);
+        assert!((month_to_date_spend(&conn).unwrap() - 4.5).abs() < 1e-9);
+
+        let linked: i64 = conn
+            .query_row("SELECT COUNT(*) FROM api_usage WHERE verification_id = 3", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(linked, 2);
+    }
+}
@@ -1,33 +1,200 @@
-use crate::database::{get_connection, models::{VerificationResult, CostEstimate, Verification}};
-use tauri::AppHandle;
-use serde_json::json;
-use rusqlite::OptionalExtension;
+use crate::commands::jobs::{submit_and_wait, JobAppeal, JobReporter, VerificationQueue};
+use crate::commands::settings::load_verification_settings;
+use crate::commands::tasks::load_criteria;
+use crate::commands::templates::resolve_template;
+use crate::commands::injection::save_injection_events;
+use crate::commands::timeline::save_timeline;
+use crate::commands::usage::{link_usage, month_to_date_spend, record_usage};
+use crate::database::{get_connection, models::{ActivityProfile, CascadeStage, CheatAction, Fingerprint, IdleAction, InjectionEvent, InjectionEventKind, VideoRegion, VerificationResult, CostEstimate, FrameMode, JobState, SamplingSettings, SamplingStrategyKind, ModelInfo, PolicyDecision, Verdict, VerificationJob, VerificationOutcome, ProviderKind, Verification, VerificationSettings}};
+use crate::verification::{
+    build_provider, check_minimum_duration, Appeal, merge_results, plan_windows, probe_duration_seconds,
+    deduplicate, Frame, FrameExtractor, TimeWindow, VerificationError, VerificationProvider,
+    VerificationRequest,
+};
+use crate::verification::activity;
+use crate::verification::anticheat::{self, Finding};
+use crate::verification::injection::{self, check_answer};
+use crate::verification::cascade::{triage_frames, triage_interval, triage_settings};
+use crate::verification::consensus::{self, plan_samples};
+use crate::verification::budget::{check_budget, crossed_warning, BudgetLimit, WARNING_FRACTION};
+use crate::verification::dedup::{DEFAULT_HASH_THRESHOLD, OVERSAMPLE_FACTOR};
+use crate::verification::mosaic::{compose, sample_interval, tile_budget};
+use crate::verification::sampling::build_sampler;
+use crate::verification::policy::{evaluate, LocalFindings};
+use crate::verification::pricing::{estimate_cost, DEFAULT_ESTIMATE_MODEL, DEFAULT_VIDEO_SIZE};
+use crate::verification::provider::MAX_FRAMES_PER_REQUEST;
+use crate::verification::stamp::stamp_frame;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+/// Never sample frames more often than this
+const FRAME_INTERVAL_SECONDS: u32 = 10;
+
+/// Emitted when spend crosses the budget warning threshold
+pub const BUDGET_WARNING_EVENT: &str = "verification-budget-warning";
+
+/// Error returned to the UI by `verify_task_with_claude`, tagged so the budget refusal can be
+/// told apart and offered as an override
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerifyError {
+    BudgetExceeded {
+        limit: BudgetLimit,
+        projected_usd: f64,
+        remaining_usd: f64,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl VerifyError {
+    pub fn message(&self) -> &str {
+        match self {
+            VerifyError::BudgetExceeded { message, .. } | VerifyError::Failed { message } => message,
+        }
+    }
+}
+
+impl From<VerificationError> for VerifyError {
+    fn from(error: VerificationError) -> Self {
+        match error {
+            VerificationError::BudgetExceeded { limit, projected_usd, remaining_usd } => VerifyError::BudgetExceeded {
+                message: error.to_string(),
+                limit,
+                projected_usd,
+                remaining_usd,
+            },
+            other => VerifyError::Failed { message: describe_error(other) },
+        }
+    }
+}
+
+impl From<String> for VerifyError {
+    fn from(message: String) -> Self {
+        VerifyError::Failed { message }
+    }
+}
+
+impl From<&str> for VerifyError {
+    fn from(message: &str) -> Self {
+        VerifyError::Failed { message: message.to_string() }
+    }
+}
+
+/// Verify a task through the job queue and wait for the verdict.
+/// Use `queue_verification` to get progress events without blocking.
 #[tauri::command]
 pub async fn verify_task_with_claude(
     app: AppHandle,
+    queue: State<'_, Arc<VerificationQueue>>,
     task_id: i64,
-) -> Result<VerificationResult, String> {
-    // Get task details and API key first, then drop connection before async call
-    let (title, description, min_duration, video_path, api_key) = {
+    override_budget: Option<bool>,
+) -> Result<VerificationOutcome, VerifyError> {
+    submit_and_wait(&app, &queue, task_id, override_budget.unwrap_or(false), None).await
+}
+
+/// Re-verify a task with the user's justification and the appealed verdict as context.
+/// The new verification links back to the old one through `parent_verification_id`.
+#[tauri::command]
+pub async fn appeal_verification(
+    app: AppHandle,
+    queue: State<'_, Arc<VerificationQueue>>,
+    verification_id: i64,
+    justification: String,
+    override_budget: Option<bool>,
+) -> Result<VerificationOutcome, VerifyError> {
+    let justification = justification.trim();
+    if justification.is_empty() {
+        return Err(VerifyError::from("Explain why the verdict should be reconsidered"));
+    }
+
+    let task_id: i64 = {
         let conn = get_connection(&app).map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT task_id FROM task_verifications WHERE id = ?1",
+            [verification_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Verification not found")?
+    };
+
+    let appeal = JobAppeal {
+        parent_verification_id: verification_id,
+        justification: justification.to_string(),
+    };
+    submit_and_wait(&app, &queue, task_id, override_budget.unwrap_or(false), Some(&appeal)).await
+}
+
+/// The whole verification pipeline for one task, run by the job worker.
+/// The model's report is stored together with the policy's verdict on it.
+pub(crate) async fn run_verification(
+    app: &AppHandle,
+    job: &VerificationJob,
+    reporter: &JobReporter,
+) -> Result<VerificationOutcome, VerifyError> {
+    let task_id = job.task_id;
+    reporter.stage(JobState::Extracting, None);
+
+    // Get task details, provider settings and API key first, then drop connection before async call
+    let (title, description, min_duration, criteria, video_path, recorded_seconds, recording_id, recording_started_at, webcam_region, settings, api_key, template, appeal) = {
+        let conn = get_connection(app).map_err(|e| e.to_string())?;
 
         let mut stmt = conn
-            .prepare("SELECT title, description, min_duration, video_path FROM tasks WHERE id = ?1")
+            .prepare("SELECT title, description, min_duration, video_path, task_type FROM tasks WHERE id = ?1")
             .map_err(|e| e.to_string())?;
 
-        let (title, description, min_duration, video_path): (String, Option<String>, i64, Option<String>) = stmt
+        let (title, description, min_duration, video_path, task_type): (String, Option<String>, i64, Option<String>, Option<String>) = stmt
             .query_row([task_id], |row| {
                 Ok((
                     row.get(0)?,
                     row.get(1)?,
                     row.get(2)?,
                     row.get(3)?,
+                    row.get(4)?,
                 ))
             })
             .map_err(|e| e.to_string())?;
 
         let video_path = video_path.ok_or("No video found for this task")?;
+        let criteria = load_criteria(&conn, task_id)?;
+
+        // Durations logged while recording, used if the video can't be probed
+        let recorded_seconds: i64 = conn
+            .query_row(
+                "SELECT COALESCE(SUM(duration), 0) FROM recordings WHERE task_id = ?1 AND status = 'completed'",
+                [task_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        // The recording behind the video: when it started, so frames can be given their
+        // wall-clock time, and where the webcam sits in the combined picture
+        let recording: Option<(i64, String, Option<String>)> = conn
+            .query_row(
+                "SELECT id, start_time, webcam_region FROM recordings WHERE task_id = ?1 AND file_path = ?2 ORDER BY id DESC LIMIT 1",
+                rusqlite::params![task_id, video_path],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        let recording_started_at = recording
+            .as_ref()
+            .and_then(|(_, start, _)| DateTime::parse_from_rfc3339(start).ok())
+            .map(|start| start.with_timezone(&Local));
+        let webcam_region: Option<VideoRegion> = recording
+            .as_ref()
+            .and_then(|(_, _, region)| region.as_deref())
+            .and_then(|region| serde_json::from_str(region).ok());
+        let recording_id = recording.map(|(id, _, _)| id);
 
         // Get Claude API key
         let mut stmt = conn
@@ -38,220 +205,1150 @@ pub async fn verify_task_with_claude(
             .query_row([], |row| row.get(0))
             .map_err(|e| e.to_string())?;
 
-        let api_key = api_key.ok_or("Claude API key not set. Please configure it in settings.")?;
+        let settings = load_verification_settings(&conn)?;
+        let template = resolve_template(&conn, task_type.as_deref())?;
+
+        let appeal = match (job.parent_verification_id, &job.appeal) {
+            (Some(parent_id), Some(justification)) => Some(load_appeal(&conn, task_id, parent_id, justification)?),
+            _ => None,
+        };
 
-        (title, description, min_duration, video_path, api_key)
+        (title, description, min_duration, criteria, video_path, recorded_seconds, recording_id, recording_started_at, webcam_region, settings, api_key, template, appeal)
     }; // conn is dropped here
 
-    // Extract frames from video
-    let frames = extract_video_frames(app.clone(), video_path.clone(), 10).await?;
+    // Fail early on a missing API key before doing any FFmpeg work
+    let cache_dir = model_cache_dir(app);
+    let triage_provider = if settings.cascade.enabled {
+        let triage = triage_settings(&settings);
+        Some(build_provider(&triage, api_key.clone(), cache_dir.as_deref()).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    // One provider per consensus sample; without consensus that is just the configured one
+    let plans = plan_samples(&settings);
+    let providers = plans
+        .iter()
+        .map(|plan| build_provider(&plan.settings, api_key.clone(), cache_dir.as_deref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let _unrecorded = UnrecordedSpend {
+        app,
+        task_id,
+        providers: providers.iter().map(|provider| provider.as_ref()).chain(triage_provider.as_deref()).collect(),
+    };
+
+    let video_seconds = measure_duration(video_path.clone(), recorded_seconds).await?;
+
+    // Recordings that are too short fail locally without spending any API money
+    if let Err(e) = check_minimum_duration(video_seconds, min_duration) {
+        let local = LocalFailure { job, settings: &settings, min_duration, video_seconds };
+        return local.store(app, e.to_string(), "Recording shorter than required duration");
+    }
+
+    // Idle stretches and signs of faked footage are found locally, before any API money is spent
+    let analysis = analyze_recording(app, &settings, recording_id, &video_path, video_seconds, webcam_region).await;
+    let mut local = LocalFindings::default();
+    if let Some(profile) = analysis.activity.as_ref().filter(|profile| profile.idle_ratio() > settings.max_idle_ratio) {
+        let flag = idle_summary(profile);
+        if settings.idle_action == IdleAction::Reject {
+            let local = LocalFailure { job, settings: &settings, min_duration, video_seconds };
+            return local.store(app, format!("{}, more than the allowed {:.0}%", flag, settings.max_idle_ratio * 100.0), &flag);
+        }
+        local.review_flags.push(flag);
+    }
+
+    let cheats = analysis.findings.iter().map(Finding::describe);
+    match settings.cheat_action {
+        CheatAction::Off => {}
+        CheatAction::Flag => local.review_flags.extend(cheats),
+        CheatAction::Veto => local.vetoes.extend(cheats),
+    }
+
+    // Refuse before extracting frames if the projected cost breaks a budget limit
+    let window_seconds = settings.chunk_minutes.saturating_mul(60);
+    let projected: f64 = plans
+        .iter()
+        .map(|plan| {
+            let model = estimate_model(&plan.settings);
+            estimate_cost(video_seconds, window_seconds, FRAME_INTERVAL_SECONDS, DEFAULT_VIDEO_SIZE, &model).estimated_cost_usd
+        })
+        .sum();
+    // A cascade may pay for both stages
+    let triage_projected = triage_provider.as_ref().map_or(0.0, |_| {
+        let interval = triage_interval(video_seconds);
+        estimate_cost(video_seconds, u32::MAX, interval, DEFAULT_VIDEO_SIZE, &settings.cascade.triage_model).estimated_cost_usd
+    });
+    {
+        let conn = get_connection(app).map_err(|e| e.to_string())?;
+        let month_spent = month_to_date_spend(&conn)?;
+
+        match check_budget(&settings, projected + triage_projected, month_spent) {
+            Err(e) if job.override_budget => println!("Budget override for task {}: {}", task_id, e),
+            result => result?,
+        }
+    }
+
+    let request = VerificationRequest {
+        title,
+        description,
+        required_duration_minutes: min_duration / 60,
+        actual_duration_minutes: video_seconds / 60.0,
+        frame_interval_seconds: FRAME_INTERVAL_SECONDS,
+        frames: vec![],
+        window: None,
+        template,
+        appeal,
+        recording_started_at,
+        activity: analysis.activity,
+        canary: Some(injection::new_canary()),
+        criteria,
+    };
+
+    // Long recordings are split into windows, each with its own frame budget
+    let windows = plan_windows(video_seconds, window_seconds);
+    println!(
+        "Verifying task {} with {} provider in {} segment(s), {} sample(s)",
+        task_id,
+        providers[0].name(),
+        windows.len(),
+        providers.len()
+    );
+
+    let run = VerificationRun {
+        app,
+        task_id,
+        video_path: &video_path,
+        provider: providers[0].as_ref(),
+        settings: &settings,
+        reporter,
+        parent_verification_id: job.parent_verification_id,
+        sample_index: 0,
+        shift: 0.0,
+    };
+    let verdicts = Verdicts {
+        app,
+        job,
+        settings: &settings,
+        min_duration,
+        video_seconds,
+        template: (request.template.name.clone(), request.template.version),
+        local,
+    };
+
+    // In a cascade a cheap model looks at the whole recording first, and only an uncertain
+    // verdict is handed on to the main model
+    let mut stage = None;
+    let mut triage_verification_id = None;
+    if let Some(triage_provider) = &triage_provider {
+        let triage_run = VerificationRun { provider: triage_provider.as_ref(), ..run };
+        let triage = triage_run.triage(&request, video_seconds).await?;
+        let confidence = triage.results[0].1.confidence;
+        let escalate = settings.cascade.escalates(confidence);
+
+        reporter.stage(JobState::Parsing, None);
+        let triage_stage = if escalate { CascadeStage::Escalated } else { CascadeStage::Triage };
+        let uniform = SamplingSettings::default();
+        let sample = Sample { run: triage, model: triage_provider.model_used() };
+        let outcome = verdicts.store(vec![sample], uniform, Some(triage_stage), None)?;
+        if !escalate {
+            return Ok(outcome);
+        }
+
+        println!("Triage confidence {} is uncertain, escalating task {} to the main model", confidence, task_id);
+        stage = Some(CascadeStage::Escalation);
+        triage_verification_id = Some(outcome.verification_id);
+    }
+
+    let mut samples = Vec::with_capacity(plans.len());
+    for (index, (plan, provider)) in plans.iter().zip(&providers).enumerate() {
+        if plans.len() > 1 {
+            println!("Consensus sample {} of {}", index + 1, plans.len());
+        }
+        let sample_run = VerificationRun { provider: provider.as_ref(), sample_index: index, shift: plan.shift, ..run };
+        let windows_run = sample_run.verify_windows(&request, &windows).await?;
+        samples.push(Sample { run: windows_run, model: provider.model_used() });
+    }
+
+    reporter.stage(JobState::Parsing, None);
+    verdicts.store(samples, settings.sampling.clone(), stage, triage_verification_id)
+}
+
+/// Records provider calls that were billed but not yet recorded when a run stops early, e.g. a
+/// cancelled job aborted between a reply and `record_spend`, so the budget check still counts them
+struct UnrecordedSpend<'a> {
+    app: &'a AppHandle,
+    task_id: i64,
+    providers: Vec<&'a dyn VerificationProvider>,
+}
+
+impl Drop for UnrecordedSpend<'_> {
+    fn drop(&mut self) {
+        let usage: Vec<_> = self.providers.iter().flat_map(|provider| provider.take_usage()).collect();
+        if usage.is_empty() {
+            return;
+        }
+
+        let recorded = get_connection(self.app)
+            .map_err(|e| e.to_string())
+            .and_then(|conn| record_usage(&conn, self.task_id, &usage));
+        if let Err(e) = recorded {
+            eprintln!("Failed to record spend of unfinished verification for task {}: {}", self.task_id, e);
+        }
+    }
+}
+
+/// One model's answers for the recording
+struct Sample {
+    run: WindowsRun,
+    model: Option<String>,
+}
+
+/// Turns the model's answers from one stage into a stored verdict, together with the findings
+/// of the local checks. Several samples are combined by majority vote.
+struct Verdicts<'a> {
+    app: &'a AppHandle,
+    job: &'a VerificationJob,
+    settings: &'a VerificationSettings,
+    min_duration: i64,
+    video_seconds: f64,
+    template: (String, i64), // prompt template name and version
+    local: LocalFindings,
+}
+
+impl Verdicts<'_> {
+    fn store(
+        &self,
+        samples: Vec<Sample>,
+        sampling: SamplingSettings,
+        stage: Option<CascadeStage>,
+        triage_verification_id: Option<i64>,
+    ) -> Result<VerificationOutcome, VerifyError> {
+        let task_id = self.job.task_id;
+        let conn = get_connection(self.app).map_err(|e| e.to_string())?;
+        let consensus = samples.len() > 1;
+
+        let mut stats = FrameStats::default();
+        let mut cost_usd = 0.0;
+        let mut usage_ids = Vec::new();
+        let mut injection_events = Vec::new();
+        let mut chunk_ids = Vec::new();
+        let mut labelled = Vec::with_capacity(samples.len());
+        let mut models: Vec<String> = Vec::new();
+        let mut sample_ids = Vec::new();
+
+        for (index, sample) in samples.into_iter().enumerate() {
+            let WindowsRun {
+                mut results,
+                stats: sample_stats,
+                usage_ids: sample_usage,
+                injection_events: events,
+                cost_usd: sample_cost,
+                chunk_ids: sample_chunks,
+            } = sample.run;
+            let result = if results.len() > 1 {
+                merge_results(&results, self.min_duration / 60)
+            } else {
+                results.remove(0).1
+            };
+
+            stats.add(sample_stats);
+            cost_usd += sample_cost;
+            injection_events.extend(events);
+            if let Some(model) = &sample.model {
+                if !models.contains(model) {
+                    models.push(model.clone());
+                }
+            }
+
+            // Samples are stored before the combined verdict, so it stays the task's latest
+            if consensus {
+                let details = RunDetails {
+                    stats: Some(sample_stats),
+                    model: sample.model.clone(),
+                    sampling: Some(sampling.clone()),
+                    cost_usd: Some(sample_cost),
+                    sample_index: Some(index),
+                    ..self.details()
+                };
+                let decision = evaluate(&self.settings.policy, &result, self.min_duration as f64 / 60.0, &self.local);
+                let sample_id = store_verification(&conn, task_id, &result, &decision, &details)?;
+                link_usage(&conn, &sample_usage, sample_id)?;
+                link_chunks(&conn, &sample_chunks, sample_id)?;
+                sample_ids.push(sample_id);
+            } else {
+                usage_ids = sample_usage;
+                chunk_ids = sample_chunks;
+            }
+
+            let label = match &sample.model {
+                Some(model) => format!("Sample {} ({})", index + 1, model),
+                None => format!("Sample {}", index + 1),
+            };
+            labelled.push((label, result));
+        }
+
+        let mut result = if consensus {
+            consensus::combine(&labelled)
+        } else {
+            labelled.remove(0).1
+        };
+
+        // Possible prompt injections hold back a pass; a leaked canary means the answer can't be trusted
+        let mut local = self.local.clone();
+        for event in &injection_events {
+            let reason = injection::describe(event);
+            match event.kind {
+                InjectionEventKind::CanaryLeaked => local.vetoes.push(reason),
+                _ => local.review_flags.push(reason),
+            }
+        }
+        for finding in local.review_flags.iter().chain(&local.vetoes) {
+            if !result.issues.contains(finding) {
+                result.issues.push(finding.clone());
+            }
+        }
+
+        println!("Sent {} frames, dropped {} near-duplicates", stats.sent, stats.dropped);
 
-    // Get video duration (for now, we'll use min_duration as placeholder)
-    let actual_duration_minutes = min_duration as f64 / 60.0;
+        let details = RunDetails {
+            stats: Some(stats),
+            model: (!models.is_empty()).then(|| models.join(", ")),
+            sampling: Some(sampling),
+            stage,
+            cost_usd: Some(cost_usd),
+            triage_verification_id,
+            ..self.details()
+        };
+        let decision = evaluate(&self.settings.policy, &result, self.min_duration as f64 / 60.0, &local);
+        let stage_label = stage.map(|stage| format!(" [{}]", stage.as_str())).unwrap_or_default();
+        println!("Task {} verdict{}: {} ({})", task_id, stage_label, decision.verdict.as_str(), decision.reasons.join("; "));
+        let verification_id = store_verification(&conn, task_id, &result, &decision, &details)?;
+        link_usage(&conn, &usage_ids, verification_id)?;
+        save_injection_events(&conn, verification_id, &injection_events)?;
+        link_samples(&conn, &sample_ids, verification_id)?;
+        link_chunks(&conn, &chunk_ids, verification_id)?;
 
-    // Send frames to Claude API
-    let result = send_to_claude_api(
-        &api_key,
-        &title,
-        description.as_deref(),
-        min_duration / 60,
-        actual_duration_minutes,
-        frames,
+        Ok(VerificationOutcome { verification_id, result, decision })
+    }
+
+    /// Details shared by every row stored for this job
+    fn details(&self) -> RunDetails {
+        RunDetails {
+            video_seconds: self.video_seconds,
+            template: Some(self.template.clone()),
+            parent_verification_id: self.job.parent_verification_id,
+            appeal: self.job.appeal.clone(),
+            ..RunDetails::default()
+        }
+    }
+}
+
+/// Point consensus samples at the combined verdict they were counted in
+fn link_samples(conn: &Connection, sample_ids: &[i64], verification_id: i64) -> Result<(), String> {
+    for sample_id in sample_ids {
+        conn.execute(
+            "UPDATE task_verifications SET consensus_id = ?1 WHERE id = ?2",
+            rusqlite::params![verification_id, sample_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Point the saved chunks a verdict was built from at it, so later runs don't resume them
+fn link_chunks(conn: &Connection, chunk_ids: &[i64], verification_id: i64) -> Result<(), String> {
+    for chunk_id in chunk_ids {
+        conn.execute(
+            "UPDATE verification_chunks SET verification_id = ?1 WHERE id = ?2",
+            rusqlite::params![verification_id, chunk_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// A recording that fails before any model call, stored with the policy's verdict like any other
+struct LocalFailure<'a> {
+    job: &'a VerificationJob,
+    settings: &'a VerificationSettings,
+    min_duration: i64,
+    video_seconds: f64,
+}
+
+impl LocalFailure<'_> {
+    fn store(&self, app: &AppHandle, explanation: String, issue: &str) -> Result<VerificationOutcome, VerifyError> {
+        println!("Task {} failed locally: {}", self.job.task_id, explanation);
+        let result = VerificationResult {
+            verified: false,
+            confidence: 100,
+            time_on_task_minutes: 0.0,
+            explanation,
+            issues: vec![issue.to_string()],
+            timeline: vec![],
+            criteria: vec![],
+        };
+
+        let conn = get_connection(app).map_err(|e| e.to_string())?;
+        let details = RunDetails {
+            video_seconds: self.video_seconds,
+            parent_verification_id: self.job.parent_verification_id,
+            appeal: self.job.appeal.clone(),
+            ..RunDetails::default()
+        };
+        let decision = evaluate(&self.settings.policy, &result, self.min_duration as f64 / 60.0, &LocalFindings::default());
+        let verification_id = store_verification(&conn, self.job.task_id, &result, &decision, &details)?;
+        Ok(VerificationOutcome { verification_id, result, decision })
+    }
+}
+
+/// Earlier recordings checked for replayed footage, newest first
+const MAX_EARLIER_RECORDINGS: usize = 50;
+
+/// Earlier recordings without a fingerprint that are fingerprinted per run, so recordings made
+/// before the check existed are caught up gradually
+const MAX_FINGERPRINT_BACKFILL: usize = 3;
+
+/// What the local checks found in a recording
+#[derive(Debug, Default)]
+struct LocalAnalysis {
+    activity: Option<ActivityProfile>,
+    findings: Vec<Finding>,
+}
+
+/// An earlier recording that still needs a fingerprint
+struct Backfill {
+    id: i64,
+    file_path: String,
+    duration: f64,
+    webcam_region: Option<VideoRegion>,
+}
+
+/// Earlier recordings to compare against: the fingerprinted ones, plus the most recent ones still
+/// missing a fingerprint whose video is still on disk
+#[derive(Default)]
+struct EarlierRecordings {
+    fingerprints: Vec<(i64, Fingerprint)>,
+    backfill: Vec<Backfill>,
+}
+
+fn load_earlier_recordings(conn: &Connection, recording_id: Option<i64>) -> Result<EarlierRecordings, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_path, duration, webcam_region, fingerprint FROM recordings
+             WHERE id != ?1 AND status = 'completed' ORDER BY id DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![recording_id.unwrap_or(-1), MAX_EARLIER_RECORDINGS as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut fingerprints = Vec::new();
+    let mut backfill = Vec::new();
+    for (id, file_path, duration, webcam_region, fingerprint) in rows {
+        match fingerprint.and_then(|json| serde_json::from_str(&json).ok()) {
+            Some(fingerprint) => fingerprints.push((id, fingerprint)),
+            None if backfill.len() < MAX_FINGERPRINT_BACKFILL && std::path::Path::new(&file_path).exists() => {
+                backfill.push(Backfill {
+                    id,
+                    file_path,
+                    duration: duration as f64,
+                    webcam_region: webcam_region.and_then(|json| serde_json::from_str(&json).ok()),
+                });
+            }
+            None => {}
+        }
+    }
+
+    Ok(EarlierRecordings { fingerprints, backfill })
+}
+
+/// Run the idle and anti-cheat checks enabled in settings over one low-resolution pass of the
+/// recording, and keep the profile and fingerprint on its recordings row. A failed analysis is
+/// logged and skipped rather than failing the verification.
+async fn analyze_recording(
+    app: &AppHandle,
+    settings: &VerificationSettings,
+    recording_id: Option<i64>,
+    video_path: &str,
+    video_seconds: f64,
+    webcam_region: Option<VideoRegion>,
+) -> LocalAnalysis {
+    let check_idle = settings.idle_action != IdleAction::Off;
+    let check_cheats = settings.cheat_action != CheatAction::Off;
+    if !check_idle && !check_cheats {
+        return LocalAnalysis::default();
+    }
+
+    let earlier = match get_connection(app).map_err(|e| e.to_string()) {
+        Ok(conn) if check_cheats => load_earlier_recordings(&conn, recording_id),
+        Ok(_) => Ok(EarlierRecordings::default()),
+        Err(e) => Err(e),
+    };
+    let EarlierRecordings { fingerprints: mut earlier, backfill } = earlier.unwrap_or_else(|e| {
+        eprintln!("Failed to load earlier recordings: {}", e);
+        EarlierRecordings::default()
+    });
+
+    let video_path = video_path.to_string();
+    let analyzed = tokio::task::spawn_blocking(move || {
+        let (frames, interval) = activity::analysis_frames(&video_path, video_seconds)?;
+        let activity = check_idle.then(|| activity::analyze(&frames, interval, webcam_region)).transpose()?;
+        if !check_cheats {
+            return Ok((LocalAnalysis { activity, findings: Vec::new() }, None, Vec::new()));
+        }
+
+        let mut backfilled = Vec::new();
+        for old in backfill {
+            let fingerprinted = activity::analysis_frames(&old.file_path, old.duration)
+                .and_then(|(frames, interval)| anticheat::fingerprint(&frames, interval, old.webcam_region));
+            match fingerprinted {
+                Ok(fingerprint) => backfilled.push((old.id, fingerprint)),
+                Err(e) => eprintln!("Failed to fingerprint recording {}: {}", old.id, e),
+            }
+        }
+        earlier.extend(backfilled.iter().cloned());
+
+        let fingerprint = anticheat::fingerprint(&frames, interval, webcam_region)?;
+        let mut findings = anticheat::find_repeats(&fingerprint);
+        findings.extend(anticheat::find_replays(&fingerprint, &earlier));
+        if let Some(webcam) = webcam_region {
+            findings.extend(anticheat::find_frozen_webcam(&frames, webcam)?);
+        }
+
+        Ok::<_, VerificationError>((LocalAnalysis { activity, findings }, Some(fingerprint), backfilled))
+    })
+    .await;
+
+    let (analysis, fingerprint, backfilled) = match analyzed {
+        Ok(Ok(analyzed)) => analyzed,
+        Ok(Err(e)) => {
+            eprintln!("Local recording analysis failed: {}", e);
+            return LocalAnalysis::default();
+        }
+        Err(e) => {
+            eprintln!("Local recording analysis task failed: {}", e);
+            return LocalAnalysis::default();
+        }
+    };
+
+    if let Err(e) = save_analysis(app, recording_id, &analysis, fingerprint.as_ref(), &backfilled) {
+        eprintln!("Failed to store recording analysis: {}", e);
+    }
+
+    analysis
+}
+
+fn save_analysis(
+    app: &AppHandle,
+    recording_id: Option<i64>,
+    analysis: &LocalAnalysis,
+    fingerprint: Option<&Fingerprint>,
+    backfilled: &[(i64, Fingerprint)],
+) -> Result<(), String> {
+    let conn = get_connection(app).map_err(|e| e.to_string())?;
+    for (id, fingerprint) in backfilled {
+        conn.execute(
+            "UPDATE recordings SET fingerprint = ?1 WHERE id = ?2",
+            rusqlite::params![serde_json::to_string(fingerprint).map_err(|e| e.to_string())?, id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    if let Some(id) = recording_id {
+        let activity = analysis.activity.as_ref().map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+        let fingerprint = fingerprint.map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE recordings SET activity_profile = COALESCE(?1, activity_profile), fingerprint = COALESCE(?2, fingerprint) WHERE id = ?3",
+            rusqlite::params![activity, fingerprint, id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// One-line description of how idle a recording was, used as an issue and review reason
+fn idle_summary(profile: &ActivityProfile) -> String {
+    let watched = if profile.webcam_checked { "screen or webcam" } else { "screen" };
+    format!(
+        "Mostly idle: no {} activity for {:.0}% of the recording",
+        watched,
+        profile.idle_ratio() * 100.0
     )
-    .await?;
+}
 
-    // Store verification result in database
-    let verification_json = serde_json::to_string(&result).map_err(|e| e.to_string())?;
+/// Per-window verdicts plus what it took to get them
+struct WindowsRun {
+    results: Vec<(TimeWindow, VerificationResult)>,
+    stats: FrameStats,
+    usage_ids: Vec<i64>, // api_usage rows written during this run
+    injection_events: Vec<InjectionEvent>,
+    cost_usd: f64,       // spend of the requests made during this run
+    chunk_ids: Vec<i64>, // saved chunks the results came from, resumed or new
+}
+
+/// How many frames were uploaded and how many were dropped as near-duplicates
+#[derive(Debug, Clone, Copy, Default)]
+struct FrameStats {
+    sent: usize,
+    dropped: usize,
+}
+
+impl FrameStats {
+    fn add(&mut self, other: FrameStats) {
+        self.sent += other.sent;
+        self.dropped += other.dropped;
+    }
+}
+
+/// Turn a provider error into a message for the UI, logging the raw reply of unparseable responses
+fn describe_error(error: VerificationError) -> String {
+    if let VerificationError::ResponseParse { raw, .. } = &error {
+        eprintln!("Unparseable verification response:\n{}", raw);
+    }
+    error.to_string()
+}
+
+/// What one verification run works with
+struct VerificationRun<'a> {
+    app: &'a AppHandle,
+    task_id: i64,
+    video_path: &'a str,
+    provider: &'a dyn VerificationProvider,
+    settings: &'a VerificationSettings,
+    reporter: &'a JobReporter,
+    parent_verification_id: Option<i64>, // verdict being appealed, if any
+    sample_index: usize, // consensus sample this run produces
+    shift: f64,          // share of the frame interval the sampled moments are moved by
+}
+
+impl VerificationRun<'_> {
+    /// Verify each window separately. When the recording has several windows, every result is saved
+    /// as soon as it arrives, so a failed run resumes where it stopped.
+    async fn verify_windows(
+        &self,
+        base_request: &VerificationRequest,
+        windows: &[TimeWindow],
+    ) -> Result<WindowsRun, String> {
+        let VerificationRun { app, task_id, video_path, provider, settings, reporter, parent_verification_id, sample_index, shift } =
+            *self;
+
+        let chunked = windows.len() > 1;
+        let model = provider.model_used().unwrap_or_else(|| provider.name().to_string());
+        let fingerprint = chunk_fingerprint(settings, &model, base_request, parent_verification_id, shift);
+        let mut saved = if chunked {
+            let conn = get_connection(app).map_err(|e| e.to_string())?;
+            load_pending_chunks(&conn, task_id, video_path, windows, sample_index, &fingerprint)?
+        } else {
+            HashMap::new()
+        };
+
+        let mut results = Vec::with_capacity(windows.len());
+        let mut total_stats = FrameStats::default();
+        let mut usage_ids = Vec::new();
+        let mut injection_events = Vec::new();
+        let mut cost_usd = 0.0;
+        let mut chunk_ids = Vec::new();
+
+        for window in windows {
+            if let Some((chunk_id, chunk)) = saved.remove(&window.index) {
+                println!("Segment {} ({}) already verified, skipping", window.index + 1, window.label());
+                chunk_ids.push(chunk_id);
+                total_stats.add(chunk.stats);
+                injection_events.extend(chunk.injection_events);
+                results.push((*window, chunk.result));
+                continue;
+            }
+
+            println!("Verifying segment {} of {} ({})", window.index + 1, window.count, window.label());
+
+            reporter.stage(JobState::Extracting, chunked.then_some(window));
+            let interval = window.frame_interval_seconds(FRAME_INTERVAL_SECONDS);
+            let interval = sample_interval(settings.frame_mode, interval, MAX_FRAMES_PER_REQUEST);
+            let sampled = consensus::shifted(window, interval as f64 * shift);
+            let (mut frames, stats) =
+                prepare_frames(video_path, &sampled, interval, settings, base_request.recording_started_at).await?;
+            let detected = if settings.ocr_frames { scan_for_injection(&frames).await } else { Vec::new() };
+            if settings.burn_in_timestamps {
+                frames = stamp_frames(frames, base_request.recording_started_at).await?;
+            }
+            let request = VerificationRequest {
+                frames,
+                frame_interval_seconds: interval,
+                window: chunked.then_some(*window),
+                ..base_request.clone()
+            };
+            reporter.stage(JobState::Uploading, chunked.then_some(window));
+            let outcome = provider.verify(&request).await;
+
+            // Record spend before looking at the outcome: failed calls are billed too
+            let conn = get_connection(app).map_err(|e| e.to_string())?;
+            let (ids, cost) = self.record_spend(&conn)?;
+            usage_ids.extend(ids);
+            cost_usd += cost;
+            let result = outcome.map_err(describe_error)?;
+            reporter.stage(JobState::Parsing, chunked.then_some(window));
+
+            let answer_events = check_answer(&result, base_request.canary.as_deref(), &detected);
+            let chunk = SavedChunk { result, stats, injection_events: [detected, answer_events].concat() };
+
+            if chunked {
+                chunk_ids.push(save_chunk(&conn, task_id, video_path, window, sample_index, &fingerprint, &chunk)?);
+            }
+
+            total_stats.add(chunk.stats);
+            injection_events.extend(chunk.injection_events);
+            results.push((*window, chunk.result));
+        }
+
+        Ok(WindowsRun {
+            results,
+            stats: total_stats,
+            usage_ids,
+            injection_events,
+            cost_usd,
+            chunk_ids,
+        })
+    }
+
+    /// Verify the whole recording in one request of small frames spread evenly across it
+    async fn triage(&self, base_request: &VerificationRequest, video_seconds: f64) -> Result<WindowsRun, String> {
+        let VerificationRun { app, video_path, provider, settings, reporter, .. } = *self;
+        println!("Triage with model {}", provider.model_used().unwrap_or_else(|| provider.name().to_string()));
+
+        reporter.stage(JobState::Extracting, None);
+        let path = video_path.to_string();
+        let max_dimension = settings.cascade.triage_max_dimension;
+        let mut frames = tokio::task::spawn_blocking(move || triage_frames(&path, video_seconds, max_dimension))
+            .await
+            .map_err(|e| format!("Triage frame extraction task failed: {}", e))?
+            .map_err(|e| e.to_string())?;
+        let stats = FrameStats { sent: frames.len(), dropped: 0 };
+
+        let detected = if settings.ocr_frames { scan_for_injection(&frames).await } else { Vec::new() };
+        if settings.burn_in_timestamps {
+            frames = stamp_frames(frames, base_request.recording_started_at).await?;
+        }
+        let request = VerificationRequest {
+            frames,
+            frame_interval_seconds: triage_interval(video_seconds),
+            window: None,
+            ..base_request.clone()
+        };
+
+        reporter.stage(JobState::Uploading, None);
+        let outcome = provider.verify(&request).await;
+
+        let conn = get_connection(app).map_err(|e| e.to_string())?;
+        let (usage_ids, cost_usd) = self.record_spend(&conn)?;
+        let result = outcome.map_err(describe_error)?;
+
+        let mut injection_events = check_answer(&result, base_request.canary.as_deref(), &detected);
+        injection_events.splice(0..0, detected);
+        let window = TimeWindow { index: 0, count: 1, start_seconds: 0.0, end_seconds: video_seconds };
+
+        Ok(WindowsRun {
+            results: vec![(window, result)],
+            stats,
+            usage_ids,
+            injection_events,
+            cost_usd,
+            chunk_ids: Vec::new(),
+        })
+    }
+
+    /// Record the provider's calls since the last check, warning when the budget threshold is
+    /// crossed. Failed calls are billed too, so this runs before the outcome is looked at.
+    fn record_spend(&self, conn: &Connection) -> Result<(Vec<i64>, f64), String> {
+        let usage = self.provider.take_usage();
+        let spent_before = month_to_date_spend(conn)?;
+        let usage_ids = record_usage(conn, self.task_id, &usage)?;
+        let cost = usage.iter().map(|u| u.cost_usd()).sum::<f64>();
+        warn_if_budget_crossed(self.app, self.settings, spent_before, spent_before + cost);
+        Ok((usage_ids, cost))
+    }
+}
+
+fn warn_if_budget_crossed(app: &AppHandle, settings: &VerificationSettings, spent_before: f64, spent_after: f64) {
+    if let Some(warning) = crossed_warning(settings, spent_before, spent_after) {
+        println!(
+            "Verification spend ${:.2} has reached {:.0}% of the ${:.2} monthly budget",
+            warning.month_spent_usd,
+            WARNING_FRACTION * 100.0,
+            warning.monthly_budget_usd
+        );
+        if let Err(e) = app.emit(BUDGET_WARNING_EVENT, warning) {
+            eprintln!("Failed to emit budget warning: {}", e);
+        }
+    }
+}
+
+/// Model whose prices are used for projections
+fn estimate_model(settings: &VerificationSettings) -> String {
+    match settings.provider {
+        ProviderKind::OpenaiCompatible => settings.openai_model.clone(),
+        _ => settings
+            .anthropic_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ESTIMATE_MODEL.to_string()),
+    }
+}
+
+/// Extract frames for a window and collapse near-duplicates. When deduplication is on, frames are
+/// sampled more densely so the freed budget goes to moments where the screen actually changed.
+/// In mosaic modes the frames are tiled into grids instead.
+async fn prepare_frames(
+    video_path: &str,
+    window: &TimeWindow,
+    interval: u32,
+    settings: &VerificationSettings,
+    recording_started_at: Option<DateTime<Local>>,
+) -> Result<(Vec<Frame>, FrameStats), String> {
+    let dedup_threshold = settings.dedup_threshold;
+    let sampling = &settings.sampling;
+
+    if settings.frame_mode != FrameMode::Full {
+        let mode = settings.frame_mode;
+        let frames = sample_frames(video_path, window, interval, tile_budget(mode, MAX_FRAMES_PER_REQUEST), sampling).await?;
+        let change_threshold = if dedup_threshold == 0 { DEFAULT_HASH_THRESHOLD } else { dedup_threshold };
+
+        let images = tokio::task::spawn_blocking(move || {
+            compose(frames, mode, MAX_FRAMES_PER_REQUEST, change_threshold, recording_started_at)
+        })
+        .await
+        .map_err(|e| format!("Mosaic task failed: {}", e))?
+        .map_err(|e| e.to_string())?;
+
+        let stats = FrameStats { sent: images.len(), dropped: 0 };
+        return Ok((images, stats));
+    }
+
+    // Scene-based strategies already pick distinct moments, so only uniform sampling is deduplicated
+    if dedup_threshold == 0 || sampling.strategy != SamplingStrategyKind::Uniform {
+        let frames = sample_frames(video_path, window, interval, MAX_FRAMES_PER_REQUEST, sampling).await?;
+        let stats = FrameStats { sent: frames.len(), dropped: 0 };
+        return Ok((frames, stats));
+    }
+
+    let sample_interval = (interval / OVERSAMPLE_FACTOR).max(2);
+    let frames = sample_frames(video_path, window, sample_interval, usize::MAX, sampling).await?;
+
+    let outcome = tokio::task::spawn_blocking(move || deduplicate(frames, dedup_threshold, MAX_FRAMES_PER_REQUEST))
+        .await
+        .map_err(|e| format!("Frame deduplication task failed: {}", e))?
+        .map_err(|e| e.to_string())?;
+
+    let stats = FrameStats { sent: outcome.frames.len(), dropped: outcome.dropped };
+    Ok((outcome.frames, stats))
+}
+
+/// A verified window saved while its run was still going
+struct SavedChunk {
+    result: VerificationResult,
+    stats: FrameStats,
+    injection_events: Vec<InjectionEvent>, // restored on resume, since the window isn't scanned again
+}
+
+/// What a window is verified with besides its frames' timing: the model, the task as the prompt
+/// describes it, the appeal and the frame settings. Saved chunks are only resumed by a run with
+/// the same fingerprint, so editing the task starts afresh.
+fn chunk_fingerprint(
+    settings: &VerificationSettings,
+    model: &str,
+    request: &VerificationRequest,
+    parent_verification_id: Option<i64>,
+    shift: f64,
+) -> String {
+    serde_json::json!({
+        "model": model,
+        "title": request.title,
+        "description": request.description,
+        "required_duration_minutes": request.required_duration_minutes,
+        "idle_spans": request.activity.as_ref().map(|activity| &activity.idle_spans),
+        "template": [request.template.name, request.template.version],
+        "criteria": request.criteria,
+        "parent_verification_id": parent_verification_id,
+        "appeal": request.appeal.as_ref().map(|appeal| &appeal.justification),
+        "frame_mode": settings.frame_mode,
+        "sampling": settings.sampling,
+        "dedup_threshold": settings.dedup_threshold,
+        "burn_in_timestamps": settings.burn_in_timestamps,
+        "frame_interval_seconds": FRAME_INTERVAL_SECONDS,
+        "shift": shift,
+    })
+    .to_string()
+}
+
+/// Window results from an earlier, unfinished run over the same recording and window plan
+fn load_pending_chunks(
+    conn: &Connection,
+    task_id: i64,
+    video_path: &str,
+    windows: &[TimeWindow],
+    sample_index: usize,
+    fingerprint: &str,
+) -> Result<HashMap<usize, (i64, SavedChunk)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT window_index, start_seconds, end_seconds, result, frames_sent, frames_dropped, injection_events, id
+             FROM verification_chunks
+             WHERE task_id = ?1 AND video_path = ?2 AND window_count = ?3 AND sample_index = ?4 AND fingerprint = ?5
+               AND verification_id IS NULL
+             ORDER BY id ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let params = rusqlite::params![task_id, video_path, windows.len() as i64, sample_index as i64, fingerprint];
+    let rows = stmt
+        .query_map(params, |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, String>(3)?,
+                FrameStats {
+                    sent: row.get::<_, i64>(4)? as usize,
+                    dropped: row.get::<_, i64>(5)? as usize,
+                },
+                row.get::<_, String>(6)?,
+                row.get::<_, i64>(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut saved = HashMap::new();
+    for (index, start, end, json, stats, events_json, chunk_id) in rows {
+        let Some(window) = windows.get(index as usize) else { continue };
+        let same_bounds = (window.start_seconds - start).abs() < 0.5 && (window.end_seconds - end).abs() < 0.5;
+
+        if same_bounds {
+            if let Ok(result) = serde_json::from_str::<VerificationResult>(&json) {
+                let injection_events = serde_json::from_str(&events_json).unwrap_or_default();
+                saved.insert(window.index, (chunk_id, SavedChunk { result, stats, injection_events }));
+            }
+        }
+    }
+
+    Ok(saved)
+}
+
+fn save_chunk(
+    conn: &Connection,
+    task_id: i64,
+    video_path: &str,
+    window: &TimeWindow,
+    sample_index: usize,
+    fingerprint: &str,
+    chunk: &SavedChunk,
+) -> Result<i64, String> {
+    let result_json = serde_json::to_string(&chunk.result).map_err(|e| e.to_string())?;
+    let events_json = serde_json::to_string(&chunk.injection_events).map_err(|e| e.to_string())?;
 
-    let conn = get_connection(&app).map_err(|e| e.to_string())?;
     conn.execute(
-        "INSERT INTO task_verifications (task_id, verified, ai_verification, ai_confidence, time_on_task, explanation)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        "INSERT INTO verification_chunks (task_id, video_path, window_index, window_count, start_seconds, end_seconds, result, frames_sent, frames_dropped, sample_index, injection_events, fingerprint)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
         rusqlite::params![
             task_id,
-            result.verified,
+            video_path,
+            window.index as i64,
+            window.count as i64,
+            window.start_seconds,
+            window.end_seconds,
+            result_json,
+            chunk.stats.sent as i64,
+            chunk.stats.dropped as i64,
+            sample_index as i64,
+            events_json,
+            fingerprint
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// How a verdict was produced, stored alongside it
+#[derive(Debug, Default)]
+struct RunDetails {
+    video_seconds: f64,
+    stats: Option<FrameStats>,          // None when no frames were sent
+    model: Option<String>,
+    template: Option<(String, i64)>,    // prompt template name and version
+    parent_verification_id: Option<i64>,
+    appeal: Option<String>,
+    sampling: Option<SamplingSettings>, // None when no frames were sampled
+    stage: Option<CascadeStage>,        // None outside a cascade
+    cost_usd: Option<f64>,              // None when no model was called
+    triage_verification_id: Option<i64>,
+    sample_index: Option<usize>,        // set on the samples of a consensus verification
+}
+
+/// Store the model's report with the policy's decision, and set the task status from the decision.
+/// A triage verdict handed on to the main model leaves the status to the escalation, and
+/// consensus samples leave it to the combined verdict.
+fn store_verification(
+    conn: &Connection,
+    task_id: i64,
+    result: &VerificationResult,
+    decision: &PolicyDecision,
+    details: &RunDetails,
+) -> Result<i64, String> {
+    let verification_json = serde_json::to_string(result).map_err(|e| e.to_string())?;
+    let decision_json = serde_json::to_string(decision).map_err(|e| e.to_string())?;
+    let (prompt_template, prompt_version) = details.template.clone().unzip();
+    let sampling_json = details
+        .sampling
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO task_verifications (task_id, verified, ai_verification, ai_confidence, time_on_task, explanation, video_duration, frames_sent, frames_dropped, model, prompt_template, prompt_version, parent_verification_id, appeal, verdict, policy_decision, sampling, stage, cost_usd, triage_verification_id, sample_index)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
+        rusqlite::params![
+            task_id,
+            decision.verdict == Verdict::Pass,
             verification_json,
             result.confidence,
             (result.time_on_task_minutes * 60.0) as i64,
-            result.explanation
+            result.explanation,
+            details.video_seconds.round() as i64,
+            details.stats.map(|s| s.sent as i64),
+            details.stats.map(|s| s.dropped as i64),
+            details.model,
+            prompt_template,
+            prompt_version,
+            details.parent_verification_id,
+            details.appeal,
+            decision.verdict.as_str(),
+            decision_json,
+            sampling_json,
+            details.stage.map(|stage| stage.as_str()),
+            details.cost_usd,
+            details.triage_verification_id,
+            details.sample_index.map(|index| index as i64)
         ],
     )
     .map_err(|e| e.to_string())?;
 
-    // Update task status based on verification
-    let new_status = if result.verified { "completed" } else { "failed" };
+    let verification_id = conn.last_insert_rowid();
+    save_timeline(conn, verification_id, &result.timeline)?;
+
+    if details.stage == Some(CascadeStage::Escalated) || details.sample_index.is_some() {
+        return Ok(verification_id);
+    }
+
     conn.execute(
         "UPDATE tasks SET status = ?1, updated_at = datetime('now', 'localtime') WHERE id = ?2",
-        rusqlite::params![new_status, task_id],
+        rusqlite::params![decision.verdict.task_status(), task_id],
     )
     .map_err(|e| e.to_string())?;
 
-    Ok(result)
+    Ok(verification_id)
 }
 
-async fn get_available_models(
-    client: &reqwest::Client,
-    api_key: &str,
-) -> Result<Vec<String>, String> {
-    // Query the Anthropic API for available models
-    let response = client
-        .get("https://api.anthropic.com/v1/models")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to query models API: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Models API returned error: {}", response.status()));
-    }
+/// Where fetched model lists are cached between runs
+fn model_cache_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_cache_dir().ok()
+}
 
-    let models_json: serde_json::Value = response
-        .json()
+/// Probe the combined video, falling back to the durations logged in `recordings`
+async fn measure_duration(video_path: String, recorded_seconds: i64) -> Result<f64, String> {
+    let probed = tokio::task::spawn_blocking(move || probe_duration_seconds(&video_path))
         .await
-        .map_err(|e| format!("Failed to parse models response: {}", e))?;
+        .map_err(|e| format!("Duration probe task failed: {}", e))?;
 
-    // Extract model IDs from response
-    let mut available_models: Vec<String> = models_json["data"]
-        .as_array()
-        .ok_or("Invalid models response format")?
-        .iter()
-        .filter_map(|m| m["id"].as_str().map(|s| s.to_string()))
-        .filter(|id| {
-            // Prefer Sonnet models, but also include Opus as fallback
-            (id.contains("claude") && id.contains("sonnet")) ||
-            (id.contains("claude") && id.contains("opus"))
-        })
-        .collect();
-
-    // Sort by model name (descending) to get newest first
-    // Model names are like "claude-3-5-sonnet-20241022" so alphabetical sort works
-    available_models.sort_by(|a, b| b.cmp(a));
-
-    println!("Available Claude models: {:?}", available_models);
-
-    if available_models.is_empty() {
-        return Err("No suitable Claude models found".to_string());
-    }
-
-    Ok(available_models)
-}
-
-async fn send_to_claude_api(
-    api_key: &str,
-    title: &str,
-    description: Option<&str>,
-    required_duration_minutes: i64,
-    actual_duration_minutes: f64,
-    frames: Vec<String>, // Base64 encoded frames
-) -> Result<VerificationResult, String> {
-    let client = reqwest::Client::new();
-
-    // Build messages with vision content
-    let mut content_parts: Vec<serde_json::Value> = vec![json!({
-        "type": "text",
-        "text": format!(
-            "You are verifying a productivity task completion.\n\n\
-            Task Details:\n\
-            - Title: {}\n\
-            - Description: {}\n\
-            - Required Duration: {} minutes\n\
-            - Video Duration: {:.1} minutes\n\n\
-            Analyze the provided video frames (1 frame every 10 seconds) and determine:\n\
-            1. Was the user engaged in the described task?\n\
-            2. For what percentage of the video was the task being performed?\n\
-            3. Did they meet the minimum duration requirement?\n\
-            4. Were there significant distractions or off-task behavior?\n\n\
-            Provide your response in JSON format:\n\
-            {{\n\
-              \"verified\": true/false,\n\
-              \"confidence\": 0-100,\n\
-              \"time_on_task_minutes\": number,\n\
-              \"explanation\": \"detailed explanation\",\n\
-              \"issues\": [\"issue 1\", \"issue 2\"],\n\
-              \"timeline\": [\n\
-                {{\"timestamp\": \"00:00\", \"activity\": \"description\"}}\n\
-              ]\n\
-            }}",
-            title,
-            description.unwrap_or("N/A"),
-            required_duration_minutes,
-            actual_duration_minutes
-        )
-    })];
-
-    // Add frames (limit to avoid token limits)
-    for frame_base64 in frames.iter().take(20) {
-        content_parts.push(json!({
-            "type": "image",
-            "source": {
-                "type": "base64",
-                "media_type": "image/jpeg",
-                "data": frame_base64
-            }
-        }));
+    match probed {
+        Ok(seconds) => Ok(seconds),
+        Err(e) if recorded_seconds > 0 => {
+            eprintln!("Failed to probe video duration: {}. Using recorded duration.", e);
+            Ok(recorded_seconds as f64)
+        }
+        Err(e) => Err(e.to_string()),
     }
+}
 
-    // Query API for available models, fallback to hardcoded list if that fails
-    let models_to_try = get_available_models(&client, api_key).await.unwrap_or_else(|e| {
-        eprintln!("Failed to query available models: {}. Using fallback list.", e);
-        vec![
-            "claude-3-5-sonnet-20241022".to_string(),  // Latest as of Oct 2024
-            "claude-3-5-sonnet-20240620".to_string(),  // June 2024
-            "claude-3-opus-20240229".to_string(),      // Fallback to Opus
-        ]
-    });
-
-    let mut last_error = String::new();
-
-    for model in &models_to_try {
-        let request_body = json!({
-            "model": model,
-            "max_tokens": 2048,
-            "messages": [{
-                "role": "user",
-                "content": content_parts.clone()
-            }]
-        });
-
-        let response = client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send request to Claude API: {}", e))?;
-
-        if response.status().is_success() {
-            // Model worked, continue with processing
-            let response_json: serde_json::Value = response
-                .json()
-                .await
-                .map_err(|e| format!("Failed to parse Claude API response: {}", e))?;
+/// Run frame extraction on a blocking thread so FFmpeg doesn't stall the async runtime
+async fn extract_frames(
+    video_path: String,
+    interval_seconds: u32,
+    window: Option<TimeWindow>,
+) -> Result<Vec<Frame>, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut extractor = FrameExtractor::new(video_path, interval_seconds);
+        if let Some(window) = window {
+            extractor = extractor.with_window(window.start_seconds, window.duration_seconds());
+        }
+        extractor.extract()
+    })
+    .await
+    .map_err(|e| format!("Frame extraction task failed: {}", e))?
+    .map_err(|e| e.to_string())
+}
 
-            // Extract text content from Claude's response
-            let text_content = response_json["content"][0]["text"]
-                .as_str()
-                .ok_or("Failed to extract text from Claude response")?;
+/// Pick up to `budget` frames from a window with the configured sampling strategy
+async fn sample_frames(
+    video_path: &str,
+    window: &TimeWindow,
+    interval: u32,
+    budget: usize,
+    sampling: &SamplingSettings,
+) -> Result<Vec<Frame>, String> {
+    let sampler = build_sampler(sampling);
+    let video_path = video_path.to_string();
+    let window = *window;
 
-            // Parse JSON from Claude's response
-            let verification: VerificationResult = serde_json::from_str(text_content)
-                .map_err(|e| format!("Failed to parse verification result: {}. Response: {}", e, text_content))?;
+    tokio::task::spawn_blocking(move || sampler.sample(&video_path, &window, interval, budget))
+        .await
+        .map_err(|e| format!("Frame sampling task failed: {}", e))?
+        .map_err(|e| e.to_string())
+}
 
-            return Ok(verification);
-        } else {
-            // Model didn't work, save error and try next
-            let error_text = response.text().await.unwrap_or_default();
-            last_error = format!("Model {} failed: {}", model, error_text);
-            eprintln!("{}", last_error);
-            continue;
+/// Look for instruction-like text on screen with OCR. A failed scan is logged and skipped rather
+/// than failing the verification.
+async fn scan_for_injection(frames: &[Frame]) -> Vec<InjectionEvent> {
+    let frames = frames.to_vec();
+    match tokio::task::spawn_blocking(move || injection::scan_frames(&frames)).await {
+        Ok(Ok(events)) => events,
+        Ok(Err(e)) => {
+            eprintln!("On-screen text scan failed: {}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            eprintln!("On-screen text scan task failed: {}", e);
+            Vec::new()
         }
     }
+}
 
-    // If we got here, all models failed
-    Err(format!("All models failed. Last error: {}", last_error))
+/// Burn each frame's offset and capture time into the image
+async fn stamp_frames(mut frames: Vec<Frame>, recording_started_at: Option<DateTime<Local>>) -> Result<Vec<Frame>, String> {
+    tokio::task::spawn_blocking(move || {
+        // Mosaic tiles are already labelled
+        for frame in frames.iter_mut().take(MAX_FRAMES_PER_REQUEST).filter(|f| f.tiles.is_empty()) {
+            stamp_frame(frame, recording_started_at)?;
+        }
+        Ok::<_, VerificationError>(frames)
+    })
+    .await
+    .map_err(|e| format!("Frame labelling task failed: {}", e))?
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -260,17 +1357,79 @@ pub async fn extract_video_frames(
     video_path: String,
     interval_seconds: u32,
 ) -> Result<Vec<String>, String> {
-    // For now, return empty vec - will implement actual frame extraction with ffmpeg later
-    // TODO: Use ffmpeg to extract frames at intervals and convert to base64
-    println!("Extracting frames from video: {} at {} second intervals", video_path, interval_seconds);
+    let frames = extract_frames(video_path, interval_seconds, None).await?;
+    Ok(frames.into_iter().map(|f| f.data).collect())
+}
+
+const VERIFICATION_COLUMNS: &str = "id, task_id, verified, ai_verification, ai_confidence, time_on_task, explanation, \
+    video_duration, frames_sent, frames_dropped, model, prompt_template, prompt_version, parent_verification_id, appeal, \
+    verdict, policy_decision, sampling, stage, cost_usd, triage_verification_id, consensus_id, sample_index, verified_at";
 
-    // Placeholder: In real implementation, we would:
-    // 1. Use ffmpeg to extract frames: ffmpeg -i video.mp4 -vf "fps=1/10" frame_%04d.jpg
-    // 2. Load each frame with image crate
-    // 3. Encode to base64
-    // 4. Return vector of base64 strings
+fn verification_from_row(row: &rusqlite::Row) -> rusqlite::Result<Verification> {
+    Ok(Verification {
+        id: Some(row.get(0)?),
+        task_id: row.get(1)?,
+        verified: row.get(2)?,
+        ai_verification: row.get(3)?,
+        ai_confidence: row.get(4)?,
+        time_on_task: row.get(5)?,
+        explanation: row.get(6)?,
+        video_duration: row.get(7)?,
+        frames_sent: row.get(8)?,
+        frames_dropped: row.get(9)?,
+        model: row.get(10)?,
+        prompt_template: row.get(11)?,
+        prompt_version: row.get(12)?,
+        parent_verification_id: row.get(13)?,
+        appeal: row.get(14)?,
+        verdict: row.get(15)?,
+        policy_decision: row.get(16)?,
+        sampling: row.get(17)?,
+        stage: row.get(18)?,
+        cost_usd: row.get(19)?,
+        triage_verification_id: row.get(20)?,
+        consensus_id: row.get(21)?,
+        sample_index: row.get(22)?,
+        verified_at: row.get(23)?,
+    })
+}
+
+/// The appealed verdict, which must belong to the task being re-verified
+fn load_appeal(conn: &Connection, task_id: i64, parent_id: i64, justification: &str) -> Result<Appeal, String> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT ai_verification FROM task_verifications WHERE id = ?1 AND task_id = ?2",
+            rusqlite::params![parent_id, task_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("The appealed verification no longer exists")?;
+
+    let previous = json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or("The appealed verification has no stored verdict")?;
+
+    Ok(Appeal { previous, justification: justification.to_string() })
+}
+
+/// Every verification of a task, oldest first. Appeals point at the verdict they
+/// re-examined through `parent_verification_id`, so the UI can draw the chain.
+fn verification_history(conn: &Connection, task_id: i64) -> Result<Vec<Verification>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM task_verifications WHERE task_id = ?1 ORDER BY verified_at ASC, id ASC",
+            VERIFICATION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let verifications = stmt
+        .query_map([task_id], verification_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
 
-    Ok(vec![])
+    verifications
 }
 
 #[tauri::command]
@@ -281,50 +1440,264 @@ pub async fn get_verification_status(
     let conn = get_connection(&app).map_err(|e| e.to_string())?;
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, task_id, verified, ai_verification, ai_confidence, time_on_task, explanation, verified_at
+        .prepare(&format!(
+            "SELECT {}
              FROM task_verifications
              WHERE task_id = ?1
-             ORDER BY verified_at DESC
+             ORDER BY verified_at DESC, id DESC
              LIMIT 1",
-        )
+            VERIFICATION_COLUMNS
+        ))
         .map_err(|e| e.to_string())?;
 
     let verification = stmt
-        .query_row([task_id], |row| {
-            Ok(Verification {
-                id: Some(row.get(0)?),
-                task_id: row.get(1)?,
-                verified: row.get(2)?,
-                ai_verification: row.get(3)?,
-                ai_confidence: row.get(4)?,
-                time_on_task: row.get(5)?,
-                explanation: row.get(6)?,
-                verified_at: row.get(7)?,
-            })
-        })
+        .query_row([task_id], verification_from_row)
         .optional()
         .map_err(|e| e.to_string())?;
 
     Ok(verification)
 }
 
+#[tauri::command]
+pub async fn get_verification_history(app: AppHandle, task_id: i64) -> Result<Vec<Verification>, String> {
+    let conn = get_connection(&app).map_err(|e| e.to_string())?;
+    verification_history(&conn, task_id)
+}
+
+/// Models offered by the configured provider, newest first. Served from a day-old cache unless `refresh` is set.
+#[tauri::command]
+pub async fn list_verification_models(
+    app: AppHandle,
+    refresh: Option<bool>,
+) -> Result<Vec<ModelInfo>, String> {
+    let (settings, api_key) = {
+        let conn = get_connection(&app).map_err(|e| e.to_string())?;
+        let api_key: Option<String> = conn
+            .query_row("SELECT claude_api_key FROM users WHERE id = 1", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        (load_verification_settings(&conn)?, api_key)
+    };
+
+    let provider = build_provider(&settings, api_key, model_cache_dir(&app).as_deref()).map_err(|e| e.to_string())?;
+    provider.list_models(refresh.unwrap_or(false)).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_verification_cost_estimate(
+    app: AppHandle,
     video_duration: u32,
+    frame_width: Option<u32>,
+    frame_height: Option<u32>,
+    model: Option<String>,
 ) -> Result<CostEstimate, String> {
-    // Rough estimate: 1 frame per 10 seconds
-    // Each frame ~1000 tokens (for vision)
-    // Plus ~500 tokens for text
-    let frames_count = (video_duration / 10).min(20); // Cap at 20 frames
-    let estimated_tokens = (frames_count * 1000 + 500) as i64;
-
-    // Claude 3.5 Sonnet pricing (approximate)
-    // Input: $3 per million tokens
-    let estimated_cost_usd = (estimated_tokens as f64 / 1_000_000.0) * 3.0;
-
-    Ok(CostEstimate {
-        estimated_tokens,
-        estimated_cost_usd,
-    })
+    let settings = {
+        let conn = get_connection(&app).map_err(|e| e.to_string())?;
+        load_verification_settings(&conn)?
+    };
+
+    let model = model.unwrap_or_else(|| estimate_model(&settings));
+    let video_size = (
+        frame_width.unwrap_or(DEFAULT_VIDEO_SIZE.0),
+        frame_height.unwrap_or(DEFAULT_VIDEO_SIZE.1),
+    );
+
+    Ok(estimate_cost(
+        video_duration as f64,
+        settings.chunk_minutes.saturating_mul(60),
+        FRAME_INTERVAL_SECONDS,
+        video_size,
+        &model,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+    use crate::database::models::{IdleSpan, VerificationPolicy};
+    use crate::verification::test_support::{test_request, test_result};
+
+    fn conn_with_tasks() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO tasks (id, title, due_date, min_duration) VALUES (1, 'Essay', '2024-10-17', 1800),
+                                                                         (2, 'Reading', '2024-10-17', 1800)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn store(conn: &Connection, task_id: i64, result: &VerificationResult, details: &RunDetails) -> i64 {
+        let decision = evaluate(&VerificationPolicy::default(), result, 30.0, &LocalFindings::default());
+        store_verification(conn, task_id, result, &decision, details).unwrap()
+    }
+
+    fn task_status(conn: &Connection, task_id: i64) -> String {
+        conn.query_row("SELECT status FROM tasks WHERE id = ?1", [task_id], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_appeal_links_to_parent_and_history_keeps_chain() {
+        let conn = conn_with_tasks();
+
+        let original = store(&conn, 1, &test_result(false, 80), &RunDetails::default());
+        assert_eq!(task_status(&conn, 1), "failed");
+        let appeal = load_appeal(&conn, 1, original, "  The tabs were research sources  ").unwrap();
+        assert!(!appeal.previous.verified);
+        assert!(load_appeal(&conn, 2, original, "wrong task").is_err());
+
+        let details = RunDetails {
+            parent_verification_id: Some(original),
+            appeal: Some(appeal.justification.clone()),
+            ..RunDetails::default()
+        };
+        let reverified = store(&conn, 1, &test_result(true, 80), &details);
+
+        let history = verification_history(&conn, 1).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].id, Some(original));
+        assert_eq!(history[1].id, Some(reverified));
+        assert_eq!(history[1].parent_verification_id, Some(original));
+        assert!(verification_history(&conn, 2).unwrap().is_empty());
+        assert_eq!(task_status(&conn, 1), "completed");
+    }
+
+    #[test]
+    fn test_policy_decision_is_stored_with_the_report() {
+        let conn = conn_with_tasks();
+
+        let sampling = SamplingSettings { strategy: SamplingStrategyKind::Hybrid, ..SamplingSettings::default() };
+        store(&conn, 2, &test_result(true, 40), &RunDetails { sampling: Some(sampling.clone()), ..RunDetails::default() });
+        assert_eq!(task_status(&conn, 2), "needs_review");
+
+        let stored = &verification_history(&conn, 2).unwrap()[0];
+        assert!(!stored.verified);
+        assert_eq!(stored.verdict.as_deref(), Some("needs_review"));
+
+        let decision: PolicyDecision = serde_json::from_str(stored.policy_decision.as_deref().unwrap()).unwrap();
+        assert_eq!(decision.verdict, Verdict::NeedsReview);
+        assert_eq!(decision.inputs.confidence, 40);
+        assert!(decision.inputs.model_verified);
+
+        let stored_sampling: SamplingSettings = serde_json::from_str(stored.sampling.as_deref().unwrap()).unwrap();
+        assert_eq!(stored_sampling, sampling);
+    }
+
+    #[test]
+    fn test_escalated_triage_leaves_status_to_escalation() {
+        let conn = conn_with_tasks();
+
+        let triage_details = RunDetails { stage: Some(CascadeStage::Escalated), cost_usd: Some(0.002), ..RunDetails::default() };
+        let triage = store(&conn, 1, &test_result(true, 60), &triage_details);
+        assert_eq!(task_status(&conn, 1), "pending");
+
+        let details = RunDetails {
+            stage: Some(CascadeStage::Escalation),
+            cost_usd: Some(0.05),
+            triage_verification_id: Some(triage),
+            ..RunDetails::default()
+        };
+        let escalation = store(&conn, 1, &test_result(true, 95), &details);
+        assert_eq!(task_status(&conn, 1), "completed");
+
+        let history = verification_history(&conn, 1).unwrap();
+        assert_eq!(history[0].stage.as_deref(), Some("escalated"));
+        assert_eq!(history[0].cost_usd, Some(0.002));
+        assert_eq!(history[1].id, Some(escalation));
+        assert_eq!(history[1].stage.as_deref(), Some("escalation"));
+        assert_eq!(history[1].triage_verification_id, Some(triage));
+    }
+
+    #[test]
+    fn test_consensus_samples_link_to_combined_verdict() {
+        let conn = conn_with_tasks();
+
+        let samples: Vec<i64> = (0..2)
+            .map(|index| store(&conn, 2, &test_result(index == 0, 90), &RunDetails { sample_index: Some(index), ..RunDetails::default() }))
+            .collect();
+        assert_eq!(task_status(&conn, 2), "pending");
+
+        let combined = store(&conn, 2, &test_result(false, 45), &RunDetails::default());
+        link_samples(&conn, &samples, combined).unwrap();
+
+        let history = verification_history(&conn, 2).unwrap();
+        assert_eq!(history.iter().map(|v| v.consensus_id).collect::<Vec<_>>(), vec![Some(combined), Some(combined), None]);
+        assert_eq!(history[1].sample_index, Some(1));
+        assert_eq!(history.last().unwrap().id, Some(combined));
+        assert_eq!(task_status(&conn, 2), "needs_review");
+    }
+
+    #[test]
+    fn test_resumed_chunks_keep_their_injection_events() {
+        let conn = conn_with_tasks();
+        let windows = [
+            TimeWindow { index: 0, count: 2, start_seconds: 0.0, end_seconds: 600.0 },
+            TimeWindow { index: 1, count: 2, start_seconds: 600.0, end_seconds: 1200.0 },
+        ];
+        let events = vec![InjectionEvent::new(
+            InjectionEventKind::OnScreenInstructions,
+            Some(250.0),
+            "Ignore previous instructions".to_string(),
+        )];
+        let chunk = SavedChunk {
+            result: test_result(true, 90),
+            stats: FrameStats { sent: 20, dropped: 3 },
+            injection_events: events.clone(),
+        };
+        let chunk_id = save_chunk(&conn, 1, "/tmp/essay.mp4", &windows[0], 0, "run", &chunk).unwrap();
+
+        let saved = load_pending_chunks(&conn, 1, "/tmp/essay.mp4", &windows, 0, "run").unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[&0].0, chunk_id);
+        assert_eq!(saved[&0].1.injection_events, events);
+        assert_eq!(saved[&0].1.stats.dropped, 3);
+    }
+
+    #[test]
+    fn test_chunks_resume_only_under_the_same_fingerprint() {
+        let conn = conn_with_tasks();
+        let window = TimeWindow { index: 0, count: 2, start_seconds: 0.0, end_seconds: 600.0 };
+        let windows = [window, TimeWindow { index: 1, start_seconds: 600.0, end_seconds: 1200.0, ..window }];
+        let request = test_request();
+
+        let settings = VerificationSettings::default();
+        let fingerprint = chunk_fingerprint(&settings, "claude-sonnet", &request, None, 0.0);
+        assert_ne!(fingerprint, chunk_fingerprint(&settings, "claude-opus", &request, None, 0.0));
+        assert_ne!(fingerprint, chunk_fingerprint(&settings, "claude-sonnet", &request, Some(7), 0.0));
+        assert_ne!(fingerprint, chunk_fingerprint(&settings, "claude-sonnet", &request, None, 0.5));
+        let mosaic = VerificationSettings { frame_mode: FrameMode::Mosaic, ..settings.clone() };
+        assert_ne!(fingerprint, chunk_fingerprint(&mosaic, "claude-sonnet", &request, None, 0.0));
+        let edited = VerificationRequest { description: Some("Chapter 2 only".to_string()), ..request.clone() };
+        assert_ne!(fingerprint, chunk_fingerprint(&settings, "claude-sonnet", &edited, None, 0.0));
+        let longer = VerificationRequest { required_duration_minutes: 45, ..request.clone() };
+        assert_ne!(fingerprint, chunk_fingerprint(&settings, "claude-sonnet", &longer, None, 0.0));
+        let idle = VerificationRequest {
+            activity: Some(ActivityProfile {
+                idle_spans: vec![IdleSpan { start_seconds: 60.0, end_seconds: 300.0 }],
+                ..ActivityProfile::default()
+            }),
+            ..request.clone()
+        };
+        assert_ne!(fingerprint, chunk_fingerprint(&settings, "claude-sonnet", &idle, None, 0.0));
+
+        let chunk = SavedChunk { result: test_result(true, 90), stats: FrameStats::default(), injection_events: vec![] };
+        let stale = save_chunk(&conn, 1, "/tmp/essay.mp4", &windows[0], 0, "older settings", &chunk).unwrap();
+        let used = save_chunk(&conn, 1, "/tmp/essay.mp4", &windows[0], 0, &fingerprint, &chunk).unwrap();
+
+        let saved = load_pending_chunks(&conn, 1, "/tmp/essay.mp4", &windows, 0, &fingerprint).unwrap();
+        assert_eq!(saved[&0].0, used);
+
+        // Only the chunk the verdict was built from is linked to it
+        let verification_id = store(&conn, 1, &test_result(true, 90), &RunDetails::default());
+        link_chunks(&conn, &[used], verification_id).unwrap();
+        let linked = |id: i64| -> Option<i64> {
+            conn.query_row("SELECT verification_id FROM verification_chunks WHERE id = ?1", [id], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(linked(used), Some(verification_id));
+        assert_eq!(linked(stale), None);
+        assert!(load_pending_chunks(&conn, 1, "/tmp/essay.mp4", &windows, 0, &fingerprint).unwrap().is_empty());
+    }
 }
@@ -8,10 +8,14 @@ pub struct Task {
     pub description: Option<String>,
     pub due_date: String,  // ISO 8601 format
     pub min_duration: i64, // in seconds
-    pub status: String,    // 'pending', 'in_progress', 'completed', 'failed'
+    pub status: String,    // 'pending', 'in_progress', 'completed', 'failed', 'needs_review'
     pub video_path: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(default)]
+    pub task_type: Option<String>, // selects the prompt template, e.g. "coding"
+    #[serde(default)]
+    pub criteria: Vec<String>, // success criteria the verdict is scored against, in order
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +27,54 @@ pub struct Recording {
     pub end_time: Option<String>,
     pub file_path: String,
     pub status: String,    // 'recording', 'paused', 'completed', 'processing'
+    pub webcam_region: Option<String>,    // JSON VideoRegion of the webcam tile in the combined video
+    pub activity_profile: Option<String>, // JSON ActivityProfile from the last local analysis
+    pub fingerprint: Option<String>,      // JSON Fingerprint, compared against later recordings
+}
+
+/// Part of a video frame, as fractions of its width and height
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct VideoRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A stretch where neither the screen nor the webcam showed any activity
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct IdleSpan {
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+}
+
+/// Active and idle time found by local frame differencing, before any API call
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ActivityProfile {
+    pub sample_interval_seconds: u32,
+    pub active_seconds: f64,
+    pub idle_seconds: f64,
+    pub idle_spans: Vec<IdleSpan>,
+    pub webcam_checked: bool, // false when the recording has no webcam tile
+}
+
+impl ActivityProfile {
+    pub fn idle_ratio(&self) -> f64 {
+        let total = self.active_seconds + self.idle_seconds;
+        if total > 0.0 {
+            self.idle_seconds / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Hashes of a recording's screen area sampled every `interval_seconds`, kept so later
+/// recordings can be checked for replayed footage
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Fingerprint {
+    pub interval_seconds: u32,
+    pub hashes: Vec<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,10 +86,26 @@ pub struct Verification {
     pub ai_confidence: Option<i64>,
     pub time_on_task: Option<i64>,       // in seconds
     pub explanation: Option<String>,
+    pub video_duration: Option<i64>,     // measured length of the recording in seconds
+    pub frames_sent: Option<i64>,
+    pub frames_dropped: Option<i64>,     // near-duplicate frames removed before upload
+    pub model: Option<String>,           // model that produced the verdict
+    pub prompt_template: Option<String>,
+    pub prompt_version: Option<i64>,
+    pub parent_verification_id: Option<i64>, // verdict this one re-examined on appeal
+    pub appeal: Option<String>,              // the user's justification
+    pub verdict: Option<String>,             // 'pass', 'fail' or 'needs_review'
+    pub policy_decision: Option<String>,     // JSON PolicyDecision
+    pub sampling: Option<String>,            // JSON SamplingSettings the frames were picked with
+    pub stage: Option<String>,               // cascade stage; None for a single-model verification
+    pub cost_usd: Option<f64>,               // API spend of this verdict's requests
+    pub triage_verification_id: Option<i64>, // triage verdict an escalation took over from
+    pub consensus_id: Option<i64>,           // combined verdict this sample was counted in
+    pub sample_index: Option<i64>,           // position among the consensus samples
     pub verified_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VerificationResult {
     pub verified: bool,
     pub confidence: i64,
@@ -45,12 +113,199 @@ pub struct VerificationResult {
     pub explanation: String,
     pub issues: Vec<String>,
     pub timeline: Vec<TimelineEntry>,
+    #[serde(default)]
+    pub criteria: Vec<CriterionScore>, // one per task criterion, in the task's order
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How well the recording shows one of the task's success criteria
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct CriterionScore {
+    pub criterion: String,
+    pub score: i64,                 // 0 for no evidence, 100 for clearly met
+    pub evidence_seconds: Vec<f64>, // offsets of the frames that show it
+    pub note: String,
+}
+
+/// Final verdict on a verification, decided locally by the policy from the model's report
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Pass,
+    Fail,
+    NeedsReview, // the model wasn't confident enough to decide either way
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Fail => "fail",
+            Verdict::NeedsReview => "needs_review",
+        }
+    }
+
+    /// Task status that goes with this verdict
+    pub fn task_status(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "completed",
+            Verdict::Fail => "failed",
+            Verdict::NeedsReview => "needs_review",
+        }
+    }
+}
+
+/// Thresholds the policy applies to the model's report
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct VerificationPolicy {
+    pub min_confidence: i64,          // below this the verdict is needs_review
+    pub min_time_on_task_ratio: f64,  // share of the task's min_duration that must be on task
+    pub max_issues: Option<usize>,    // more issues than this fails; None ignores the count
+    pub min_criterion_score: i64,     // every success criterion must score at least this to pass
+}
+
+impl Default for VerificationPolicy {
+    fn default() -> Self {
+        Self {
+            min_confidence: 60,
+            min_time_on_task_ratio: 1.0,
+            max_issues: None,
+            min_criterion_score: 70,
+        }
+    }
+}
+
+/// What the policy looked at, stored with the decision for auditing
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PolicyInputs {
+    pub model_verified: bool,
+    pub confidence: i64,
+    pub time_on_task_minutes: f64,
+    pub required_minutes: f64,
+    pub issue_count: usize,
+    #[serde(default)]
+    pub review_flags: Vec<String>, // local findings that keep a pass from being final
+    #[serde(default)]
+    pub vetoes: Vec<String>, // local findings that fail the verdict outright
+    #[serde(default)]
+    pub criterion_scores: Vec<i64>, // in the task's order; empty when the task has no criteria
+    pub policy: VerificationPolicy,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PolicyDecision {
+    pub verdict: Verdict,
+    pub reasons: Vec<String>, // every rule that failed, or why it passed
+    pub inputs: PolicyInputs,
+}
+
+/// Returned to the UI by a verification run: the model's report and the policy's verdict on it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerificationOutcome {
+    pub verification_id: i64,
+    pub result: VerificationResult,
+    pub decision: PolicyDecision,
+}
+
+/// What the user was doing during a stretch of the recording
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityCategory {
+    #[default]
+    OnTask,
+    OffTask,
+    Idle,  // nobody at the screen, or nothing changing
+    Break, // deliberate pause, e.g. away from the desk
+}
+
+impl ActivityCategory {
+    pub const ALL: [ActivityCategory; 4] = [
+        ActivityCategory::OnTask,
+        ActivityCategory::OffTask,
+        ActivityCategory::Idle,
+        ActivityCategory::Break,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityCategory::OnTask => "on_task",
+            ActivityCategory::OffTask => "off_task",
+            ActivityCategory::Idle => "idle",
+            ActivityCategory::Break => "break",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+}
+
+/// A stretch of the recording, with offsets in seconds from its start.
+/// Fields default so results stored before offsets existed still load.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
 pub struct TimelineEntry {
-    pub timestamp: String,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub category: ActivityCategory,
     pub activity: String,
+    pub label: Option<String>, // app or site in the foreground, e.g. "VS Code" or "youtube.com"
+}
+
+/// How a possible prompt injection was noticed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionEventKind {
+    OnScreenInstructions, // OCR found text addressed to the model in a frame
+    ModelReported,        // the model reported an injection attempt as an issue
+    CanaryLeaked,         // the answer contains the system prompt's check token
+    QuotedInjection,      // the answer repeats detected on-screen instructions
+}
+
+impl InjectionEventKind {
+    pub const ALL: [InjectionEventKind; 4] = [
+        InjectionEventKind::OnScreenInstructions,
+        InjectionEventKind::ModelReported,
+        InjectionEventKind::CanaryLeaked,
+        InjectionEventKind::QuotedInjection,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InjectionEventKind::OnScreenInstructions => "on_screen_instructions",
+            InjectionEventKind::ModelReported => "model_reported",
+            InjectionEventKind::CanaryLeaked => "canary_leaked",
+            InjectionEventKind::QuotedInjection => "quoted_injection",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == value)
+    }
+}
+
+/// A prompt-injection detection recorded on a verification
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InjectionEvent {
+    pub kind: InjectionEventKind,
+    pub offset_seconds: Option<f64>, // frame the text was seen in, when known
+    pub detail: String,              // the detected text or reported issue
+}
+
+impl InjectionEvent {
+    pub fn new(kind: InjectionEventKind, offset_seconds: Option<f64>, detail: String) -> Self {
+        Self { kind, offset_seconds, detail }
+    }
+}
+
+/// Time per category (and app or site) summed over the latest verification of each task
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimelineTotal {
+    pub category: ActivityCategory,
+    pub label: Option<String>,
+    pub total_seconds: f64,
+    pub sessions: i64, // verifications contributing to the total
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -75,4 +330,296 @@ pub struct User {
 pub struct CostEstimate {
     pub estimated_tokens: i64,
     pub estimated_cost_usd: f64,
+    pub model: String,
+    pub frames: i64,
+}
+
+/// A named, versioned verification prompt. Saving a template under an existing name adds a new version.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PromptTemplate {
+    pub id: Option<i64>,
+    pub name: String,    // task type it applies to, or "default"
+    pub version: i64,    // 0 is the built-in template
+    pub body: String,    // text with {{placeholder}} task fields
+    pub created_at: Option<String>,
+}
+
+/// A model offered by the verification provider
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ModelInfo {
+    pub id: String,
+    pub display_name: Option<String>,
+    pub created_at: Option<String>, // RFC 3339, when the provider reports it
+}
+
+/// Stage of a background verification job
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Extracting, // probing the video and extracting frames
+    Uploading,  // waiting on the model
+    Parsing,    // validating, merging and storing the verdict
+    Done,
+    Failed,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Extracting => "extracting",
+            JobState::Uploading => "uploading",
+            JobState::Parsing => "parsing",
+            JobState::Done => "done",
+            JobState::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "queued" => Some(JobState::Queued),
+            "extracting" => Some(JobState::Extracting),
+            "uploading" => Some(JobState::Uploading),
+            "parsing" => Some(JobState::Parsing),
+            "done" => Some(JobState::Done),
+            "failed" => Some(JobState::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerificationJob {
+    pub id: i64,
+    pub task_id: i64,
+    pub state: JobState,
+    pub override_budget: bool,
+    pub attempts: i64,
+    pub error: Option<String>,
+    pub verification_id: Option<i64>, // set once the job is done
+    pub parent_verification_id: Option<i64>, // set for appeals
+    pub appeal: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// This month's spend against the configured limits
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub month_spent_usd: f64,
+    pub monthly_budget_usd: Option<f64>,
+    pub remaining_usd: Option<f64>,
+    pub max_verification_cost_usd: Option<f64>,
+}
+
+/// Spend for one day, month or task
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpendSummary {
+    pub period: String,       // "2024-10-17", "2024-10" or the task title
+    pub task_id: Option<i64>, // set when grouped by task
+    pub requests: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    #[default]
+    Anthropic,
+    OpenaiCompatible, // llama.cpp server, Ollama, etc.
+    Mock,
+}
+
+/// What each image sent to the model shows
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameMode {
+    #[default]
+    Full,   // one frame per image
+    Mosaic, // labelled grids of downscaled frames
+    Mixed,  // grids, plus full frames wherever the grid shows a change
+}
+
+/// What to do with a recording the local analysis finds mostly idle
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleAction {
+    Off,    // skip the analysis
+    #[default]
+    Flag,   // verify anyway; a pass becomes needs_review
+    Reject, // fail locally without calling the API
+}
+
+/// What to do when the anti-cheat checks find replayed footage or a frozen webcam
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CheatAction {
+    Off,  // skip the checks
+    Flag, // a pass becomes needs_review
+    #[default]
+    Veto, // the verdict fails whatever the model said
+}
+
+/// Which step of a cascade produced a verification
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CascadeStage {
+    Triage,     // cheap model on low-resolution frames, final outside the uncertainty band
+    Escalated,  // triage verdict inside the band, handed on to the main model
+    Escalation, // main model on the full frame set
+}
+
+impl CascadeStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CascadeStage::Triage => "triage",
+            CascadeStage::Escalated => "escalated",
+            CascadeStage::Escalation => "escalation",
+        }
+    }
+}
+
+/// A cheap first pass that only sends uncertain recordings on to the main model
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CascadeSettings {
+    pub enabled: bool,
+    pub triage_model: String,      // model of the configured provider used for the first pass
+    pub triage_max_dimension: u32, // longest edge of triage frames, in pixels
+    pub uncertainty_min: i64,      // triage confidence from uncertainty_min to uncertainty_max escalates
+    pub uncertainty_max: i64,
+}
+
+impl Default for CascadeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            triage_model: "claude-3-5-haiku-latest".to_string(),
+            triage_max_dimension: 512,
+            uncertainty_min: 0,
+            uncertainty_max: 85,
+        }
+    }
+}
+
+impl CascadeSettings {
+    /// Whether a triage verdict at `confidence` is uncertain enough to escalate
+    pub fn escalates(&self, confidence: i64) -> bool {
+        (self.uncertainty_min..=self.uncertainty_max).contains(&confidence)
+    }
+}
+
+/// What differs between the samples of a consensus verification
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleVariation {
+    #[default]
+    Sampling, // same model, frames taken at shifted moments
+    Models,   // same frames, a different model of the configured provider per sample
+}
+
+/// Verify a recording several times and combine the answers by majority vote
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ConsensusSettings {
+    pub samples: u32, // 1 turns consensus off
+    pub variation: SampleVariation,
+    pub models: Vec<String>, // models: used in turn, one per sample
+}
+
+impl Default for ConsensusSettings {
+    fn default() -> Self {
+        Self {
+            samples: 1,
+            variation: SampleVariation::Sampling,
+            models: Vec::new(),
+        }
+    }
+}
+
+/// How the moments sent as frames are picked
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingStrategyKind {
+    #[default]
+    Uniform,     // one frame every fixed interval
+    SceneChange, // frames where FFmpeg's scene score jumps
+    Hybrid,      // uniform minimum coverage, the rest of the budget on the biggest changes
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SamplingSettings {
+    pub strategy: SamplingStrategyKind,
+    pub scene_threshold: f64,       // 0-1 scene score a frame must exceed to count as a change
+    pub min_coverage_seconds: u32,  // hybrid: at least one frame this often
+}
+
+impl Default for SamplingSettings {
+    fn default() -> Self {
+        Self {
+            strategy: SamplingStrategyKind::Uniform,
+            scene_threshold: 0.3,
+            min_coverage_seconds: 120,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct VerificationSettings {
+    pub provider: ProviderKind,
+    pub anthropic_base_url: String,
+    pub anthropic_model: Option<String>, // pinned model; None picks the newest Sonnet/Opus
+    pub openai_base_url: String,
+    pub openai_model: String,
+    pub openai_api_key: Option<String>,
+    pub chunk_minutes: u32, // long recordings are verified in windows of this length
+    pub dedup_threshold: u32, // max differing hash bits for frames to count as duplicates, 0 disables
+    pub burn_in_timestamps: bool, // draw each frame's offset and capture time onto the image
+    pub frame_mode: FrameMode,
+    pub sampling: SamplingSettings,
+    pub idle_action: IdleAction,
+    pub max_idle_ratio: f64, // share of the recording that may be idle before idle_action applies
+    pub cheat_action: CheatAction,
+    pub ocr_frames: bool, // read on-screen text with Tesseract, when installed, to spot prompt injection
+    pub cascade: CascadeSettings,
+    pub consensus: ConsensusSettings,
+    pub monthly_budget_usd: Option<f64>,        // None means no cap
+    pub max_verification_cost_usd: Option<f64>, // None means no cap
+    pub max_retries: u32, // retries for rate limits, overload and network errors
+    pub request_timeout_seconds: u64,
+    pub policy: VerificationPolicy,
+}
+
+impl Default for VerificationSettings {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::Anthropic,
+            anthropic_base_url: "https://api.anthropic.com".to_string(),
+            anthropic_model: None,
+            openai_base_url: "http://localhost:11434/v1".to_string(), // Ollama default
+            openai_model: "llava".to_string(),
+            openai_api_key: None,
+            chunk_minutes: 15,
+            dedup_threshold: crate::verification::dedup::DEFAULT_HASH_THRESHOLD,
+            burn_in_timestamps: false,
+            frame_mode: FrameMode::Full,
+            sampling: SamplingSettings::default(),
+            idle_action: IdleAction::Flag,
+            max_idle_ratio: 0.6,
+            cheat_action: CheatAction::Veto,
+            ocr_frames: true,
+            cascade: CascadeSettings::default(),
+            consensus: ConsensusSettings::default(),
+            monthly_budget_usd: None,
+            max_verification_cost_usd: None,
+            max_retries: 3,
+            request_timeout_seconds: 180,
+            policy: VerificationPolicy::default(),
+        }
+    }
 }
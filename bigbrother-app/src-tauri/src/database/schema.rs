@@ -32,6 +32,23 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // Picks the prompt template, e.g. 'coding', 'reading' or 'exercise'
+    add_column_if_missing(conn, "tasks", "task_type", "TEXT")?;
+
+    // Success criteria of a task, e.g. "Solved at least 5 practice problems", scored one by one
+    // during verification
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_criteria (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
     // Recordings table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS recordings (
@@ -47,6 +64,13 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // Where the webcam sits in the combined video, and the local activity analysis (JSON)
+    add_column_if_missing(conn, "recordings", "webcam_region", "TEXT")?;
+    add_column_if_missing(conn, "recordings", "activity_profile", "TEXT")?;
+
+    // Screen hashes used to spot this recording being replayed later (JSON)
+    add_column_if_missing(conn, "recordings", "fingerprint", "TEXT")?;
+
     // Verifications table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS task_verifications (
@@ -63,6 +87,138 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    add_column_if_missing(conn, "task_verifications", "video_duration", "INTEGER")?;
+    add_column_if_missing(conn, "task_verifications", "frames_sent", "INTEGER")?;
+    add_column_if_missing(conn, "task_verifications", "frames_dropped", "INTEGER")?;
+    add_column_if_missing(conn, "task_verifications", "model", "TEXT")?;
+    add_column_if_missing(conn, "task_verifications", "prompt_template", "TEXT")?;
+    add_column_if_missing(conn, "task_verifications", "prompt_version", "INTEGER")?;
+    // Set on a re-verification requested by an appeal, pointing at the verdict appealed against
+    add_column_if_missing(conn, "task_verifications", "parent_verification_id", "INTEGER REFERENCES task_verifications(id)")?;
+    add_column_if_missing(conn, "task_verifications", "appeal", "TEXT")?;
+    // Verdict decided by the local policy, and the inputs it was decided from (JSON)
+    add_column_if_missing(conn, "task_verifications", "verdict", "TEXT")?;
+    add_column_if_missing(conn, "task_verifications", "policy_decision", "TEXT")?;
+    // Sampling strategy and parameters the frames were picked with (JSON)
+    add_column_if_missing(conn, "task_verifications", "sampling", "TEXT")?;
+
+    // Cascade stage and spend of each verdict; an escalation points at the triage verdict it replaced
+    add_column_if_missing(conn, "task_verifications", "stage", "TEXT")?;
+    add_column_if_missing(conn, "task_verifications", "cost_usd", "REAL")?;
+    add_column_if_missing(conn, "task_verifications", "triage_verification_id", "INTEGER REFERENCES task_verifications(id)")?;
+    // Samples of a consensus verification point at the combined verdict
+    add_column_if_missing(conn, "task_verifications", "consensus_id", "INTEGER REFERENCES task_verifications(id)")?;
+    add_column_if_missing(conn, "task_verifications", "sample_index", "INTEGER")?;
+
+    // Per-window results for chunked verification, kept so an interrupted run can resume.
+    // verification_id is set once the merged result has been stored.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS verification_chunks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            verification_id INTEGER,
+            video_path TEXT NOT NULL,
+            window_index INTEGER NOT NULL,
+            window_count INTEGER NOT NULL,
+            start_seconds REAL NOT NULL,
+            end_seconds REAL NOT NULL,
+            result TEXT NOT NULL,
+            frames_sent INTEGER NOT NULL DEFAULT 0,
+            frames_dropped INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (task_id) REFERENCES tasks(id),
+            FOREIGN KEY (verification_id) REFERENCES task_verifications(id)
+        )",
+        [],
+    )?;
+    // Consensus samples of the same window are resumed separately
+    add_column_if_missing(conn, "verification_chunks", "sample_index", "INTEGER NOT NULL DEFAULT 0")?;
+    // Injection events found in the window, as JSON, so a resumed run still reports them
+    add_column_if_missing(conn, "verification_chunks", "injection_events", "TEXT NOT NULL DEFAULT '[]'")?;
+    // Settings the window was verified with, see chunk_fingerprint in commands/verification.rs
+    add_column_if_missing(conn, "verification_chunks", "fingerprint", "TEXT NOT NULL DEFAULT ''")?;
+
+    // One row per billed model call. verification_id is NULL for calls whose verification
+    // never completed; they still count towards spend. task_id has no foreign key so spend
+    // history survives deleting the task.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS api_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER,
+            verification_id INTEGER,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cost_usd REAL NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (verification_id) REFERENCES task_verifications(id)
+        )",
+        [],
+    )?;
+
+    // Background verification jobs, see commands/jobs.rs
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS verification_jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            state TEXT NOT NULL DEFAULT 'queued',
+            override_budget BOOLEAN NOT NULL DEFAULT 0,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            verification_id INTEGER,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (task_id) REFERENCES tasks(id),
+            FOREIGN KEY (verification_id) REFERENCES task_verifications(id)
+        )",
+        [],
+    )?;
+
+    add_column_if_missing(conn, "verification_jobs", "parent_verification_id", "INTEGER REFERENCES task_verifications(id)")?;
+    add_column_if_missing(conn, "verification_jobs", "appeal", "TEXT")?;
+
+    // Structured timeline of each verification, one row per stretch of the recording
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS verification_timeline (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            verification_id INTEGER NOT NULL,
+            start_seconds REAL NOT NULL,
+            end_seconds REAL NOT NULL,
+            category TEXT NOT NULL,
+            activity TEXT NOT NULL,
+            label TEXT,
+            FOREIGN KEY (verification_id) REFERENCES task_verifications(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Possible prompt injections noticed while verifying, one row per detection
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS injection_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            verification_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            offset_seconds REAL,
+            detail TEXT NOT NULL,
+            FOREIGN KEY (verification_id) REFERENCES task_verifications(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Verification prompts, one row per version. Rows are never edited, so a stored
+    // verification's (prompt_template, prompt_version) always points at the text that was sent.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS prompt_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (name, version)
+        )",
+        [],
+    )?;
+
     // Verifiers table (for future use)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS verifiers (
@@ -77,6 +233,16 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // Settings table (key/value, values are JSON)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+        [],
+    )?;
+
     // Create default user if not exists
     conn.execute(
         "INSERT OR IGNORE INTO users (id, email, username, password_hash)
@@ -100,5 +266,80 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_task_criteria_task_id ON task_criteria(task_id, position)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_verification_chunks_task_id ON verification_chunks(task_id)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_verification_jobs_state ON verification_jobs(state)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_verification_timeline_verification_id ON verification_timeline(verification_id)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_injection_events_verification_id ON injection_events(verification_id)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_api_usage_created_at ON api_usage(created_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_api_usage_task_id ON api_usage(task_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Add a column to an existing table; SQLite has no `ADD COLUMN IF NOT EXISTS`
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<String>>>()?
+        .iter()
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_tables_is_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        create_tables(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(task_verifications)")
+            .unwrap()
+            .query_map([], |row| row.get(1))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert!(columns.contains(&"video_duration".to_string()));
+    }
+}
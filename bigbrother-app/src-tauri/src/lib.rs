@@ -1,8 +1,9 @@
 mod database;
 mod commands;
 mod recording;
+mod verification;
 
-use commands::{tasks, recording as recording_commands, verification, settings, utils};
+use commands::{tasks, recording as recording_commands, verification as verification_commands, jobs, settings, templates, timeline, injection, usage, utils};
 use std::sync::Arc;
 use tauri::Manager;
 
@@ -20,6 +21,13 @@ pub fn run() {
             let recording_state = Arc::new(recording_commands::RecordingState::new());
             app.manage(recording_state);
 
+            // Start the verification worker and pick up jobs interrupted by the last quit
+            let verification_queue = jobs::VerificationQueue::start(app.handle().clone());
+            if let Err(e) = jobs::resume_unfinished_jobs(app.handle(), &verification_queue) {
+                eprintln!("Failed to resume verification jobs: {}", e);
+            }
+            app.manage(verification_queue);
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -41,13 +49,35 @@ pub fn run() {
             recording_commands::enumerate_displays,
             recording_commands::enumerate_webcams,
             // Verification commands
-            verification::verify_task_with_claude,
-            verification::get_verification_status,
-            verification::extract_video_frames,
-            verification::get_verification_cost_estimate,
+            verification_commands::verify_task_with_claude,
+            verification_commands::get_verification_status,
+            verification_commands::get_verification_history,
+            verification_commands::appeal_verification,
+            verification_commands::extract_video_frames,
+            verification_commands::get_verification_cost_estimate,
+            verification_commands::list_verification_models,
+            jobs::queue_verification,
+            jobs::cancel_verification,
+            jobs::list_verification_jobs,
             // Settings commands
             settings::set_claude_api_key,
             settings::get_claude_api_key,
+            settings::get_verification_settings,
+            settings::update_verification_settings,
+            // Timeline commands
+            timeline::get_verification_timeline,
+            timeline::get_timeline_totals,
+            // Prompt injection commands
+            injection::get_injection_events,
+            // Prompt template commands
+            templates::list_prompt_templates,
+            templates::get_prompt_template,
+            templates::save_prompt_template,
+            // Spend commands
+            usage::get_spend_by_day,
+            usage::get_spend_by_month,
+            usage::get_spend_by_task,
+            usage::get_budget_status,
             // Utility commands
             utils::open_video_file,
         ])
@@ -1,3 +1,4 @@
+use crate::database::models::VideoRegion;
 use std::process::Command;
 use std::path::Path;
 
@@ -74,6 +75,27 @@ impl VideoCombiner {
         Ok(())
     }
 
+    /// Where input `index` ends up in the output of `combine_grid`, as fractions of the frame
+    pub fn grid_cell(&self, index: usize) -> Option<VideoRegion> {
+        let num_videos = self.input_files.len();
+        if index >= num_videos {
+            return None;
+        }
+
+        let (rows, cols) = match num_videos {
+            1 => (1, 1),
+            2 => (1, 2), // side by side
+            n => self.calculate_grid_dimensions(n),
+        };
+
+        Some(VideoRegion {
+            x: (index % cols) as f64 / cols as f64,
+            y: (index / cols) as f64 / rows as f64,
+            width: 1.0 / cols as f64,
+            height: 1.0 / rows as f64,
+        })
+    }
+
     fn calculate_grid_dimensions(&self, num_videos: usize) -> (usize, usize) {
         match num_videos {
             1 => (1, 1),
@@ -243,4 +265,16 @@ mod tests {
         assert_eq!(combiner.calculate_grid_dimensions(5), (2, 3));
         assert_eq!(combiner.calculate_grid_dimensions(9), (3, 3));
     }
+
+    #[test]
+    fn test_grid_cell_of_last_input() {
+        let files = |n: usize| (0..n).map(|i| format!("{}.mp4", i)).collect::<Vec<_>>();
+
+        let pair = VideoCombiner::new(files(2), String::new());
+        assert_eq!(pair.grid_cell(1), Some(VideoRegion { x: 0.5, y: 0.0, width: 0.5, height: 1.0 }));
+
+        let three = VideoCombiner::new(files(3), String::new());
+        assert_eq!(three.grid_cell(2), Some(VideoRegion { x: 0.0, y: 0.5, width: 0.5, height: 0.5 }));
+        assert_eq!(three.grid_cell(3), None);
+    }
 }
@@ -0,0 +1,216 @@
+use super::error::VerificationError;
+use super::frames::{decode_rgb, Frame, FrameExtractor};
+use crate::database::models::{ActivityProfile, IdleSpan, VideoRegion};
+use image::{imageops, GrayImage};
+
+/// Differencing needs no detail, and long recordings give many samples
+const ANALYSIS_DIMENSION: u32 = 480;
+
+/// Sample at most this often...
+const MIN_SAMPLE_SECONDS: u32 = 5;
+
+/// ...and take at most this many samples
+const MAX_SAMPLES: f64 = 1000.0;
+
+/// A pixel has changed when its brightness moves by more than this
+const PIXEL_CHANGE: u8 = 20;
+
+/// Below this share of changed pixels the screen is static; a blinking cursor or clock stays under it
+const SCREEN_CHANGE_FRACTION: f64 = 0.001;
+
+/// Someone in front of the webcam moves at least this share of its pixels
+const WEBCAM_CHANGE_FRACTION: f64 = 0.002;
+
+/// A webcam tile this dark and flat shows nothing: camera off, covered or no signal
+const BLANK_MAX_MEAN: f64 = 16.0;
+const BLANK_MAX_STDDEV: f64 = 8.0;
+
+/// Shorter pauses are normal reading or thinking time and don't count as idle
+pub const MIN_IDLE_SECONDS: f64 = 60.0;
+
+/// Pixel rectangle `(x0, y0, x1, y1)` of a region in an image of the given size
+pub(crate) fn pixel_rect(region: &VideoRegion, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let scale = |fraction: f64, size: u32| ((fraction * size as f64).round() as u32).min(size);
+    (
+        scale(region.x, width),
+        scale(region.y, height),
+        scale(region.x + region.width, width),
+        scale(region.y + region.height, height),
+    )
+}
+
+fn contains((x0, y0, x1, y1): (u32, u32, u32, u32), x: u32, y: u32) -> bool {
+    x >= x0 && x < x1 && y >= y0 && y < y1
+}
+
+/// Share of pixels inside `rect` (or outside it, with `inside` false) whose brightness changed
+fn changed_fraction(prev: &GrayImage, cur: &GrayImage, rect: (u32, u32, u32, u32), inside: bool) -> f64 {
+    let mut total = 0u64;
+    let mut changed = 0u64;
+
+    for (x, y, pixel) in cur.enumerate_pixels() {
+        if contains(rect, x, y) != inside {
+            continue;
+        }
+        total += 1;
+        if pixel[0].abs_diff(prev.get_pixel(x, y)[0]) > PIXEL_CHANGE {
+            changed += 1;
+        }
+    }
+
+    if total == 0 {
+        0.0
+    } else {
+        changed as f64 / total as f64
+    }
+}
+
+/// Whether the pixels in `rect` are too dark and flat to show anything
+pub(crate) fn is_blank(img: &GrayImage, (x0, y0, x1, y1): (u32, u32, u32, u32)) -> bool {
+    let values: Vec<f64> = (y0..y1)
+        .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+        .map(|(x, y)| img.get_pixel(x, y)[0] as f64)
+        .collect();
+    if values.is_empty() {
+        return true;
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    mean <= BLANK_MAX_MEAN && variance.sqrt() <= BLANK_MAX_STDDEV
+}
+
+/// How often to sample a recording of `duration_seconds`
+pub fn sample_interval(duration_seconds: f64) -> u32 {
+    ((duration_seconds / MAX_SAMPLES).ceil() as u32).max(MIN_SAMPLE_SECONDS)
+}
+
+/// Compare consecutive frames and find stretches of at least `MIN_IDLE_SECONDS` where the screen
+/// didn't change and the webcam tile, if any, was blank or still
+pub fn analyze(frames: &[Frame], interval: u32, webcam: Option<VideoRegion>) -> Result<ActivityProfile, VerificationError> {
+    let mut spans: Vec<IdleSpan> = Vec::new();
+    let mut current: Option<IdleSpan> = None;
+    let mut prev: Option<(f64, GrayImage)> = None;
+
+    for frame in frames {
+        let img = imageops::grayscale(&decode_rgb(frame)?);
+
+        if let Some((prev_offset, prev_img)) = &prev {
+            let rect = webcam.map(|region| pixel_rect(&region, img.width(), img.height()));
+            // Everything outside the webcam tile is screen; an empty rect leaves the whole frame
+            let screen_still =
+                changed_fraction(prev_img, &img, rect.unwrap_or((0, 0, 0, 0)), false) < SCREEN_CHANGE_FRACTION;
+            let webcam_still = rect.is_none_or(|rect| {
+                is_blank(&img, rect) || changed_fraction(prev_img, &img, rect, true) < WEBCAM_CHANGE_FRACTION
+            });
+
+            if screen_still && webcam_still {
+                let span = current.get_or_insert(IdleSpan { start_seconds: *prev_offset, end_seconds: *prev_offset });
+                span.end_seconds = frame.offset_seconds;
+            } else if let Some(span) = current.take() {
+                spans.push(span);
+            }
+        }
+
+        prev = Some((frame.offset_seconds, img));
+    }
+    spans.extend(current);
+    spans.retain(|span| span.end_seconds - span.start_seconds >= MIN_IDLE_SECONDS);
+
+    let covered = match (frames.first(), frames.last()) {
+        (Some(first), Some(last)) => last.offset_seconds + interval as f64 - first.offset_seconds,
+        _ => 0.0,
+    };
+    let idle_seconds: f64 = spans.iter().map(|span| span.end_seconds - span.start_seconds).sum();
+
+    Ok(ActivityProfile {
+        sample_interval_seconds: interval,
+        active_seconds: (covered - idle_seconds).max(0.0),
+        idle_seconds,
+        idle_spans: spans,
+        webcam_checked: webcam.is_some(),
+    })
+}
+
+/// Sample a whole recording at low resolution for the local checks. Returns the frames and the
+/// interval they were taken at.
+pub fn analysis_frames(video_path: &str, duration_seconds: f64) -> Result<(Vec<Frame>, u32), VerificationError> {
+    let interval = sample_interval(duration_seconds);
+    let mut extractor = FrameExtractor::new(video_path.to_string(), interval);
+    extractor.max_dimension = ANALYSIS_DIMENSION;
+
+    Ok((extractor.extract()?, interval))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::test_frame;
+    use image::{Rgb, RgbImage};
+
+    const WEBCAM: VideoRegion = VideoRegion { x: 0.5, y: 0.0, width: 0.5, height: 1.0 };
+
+    /// A 160x90 frame whose left half shows a block at `screen` and right half, the webcam
+    /// tile, a block at `webcam` or nothing
+    fn frame(offset_seconds: f64, screen: u32, webcam: Option<u32>) -> Frame {
+        let img = RgbImage::from_fn(160, 90, |x, y| {
+            let on_block = |left: u32| x >= left && x < left + 10 && (30..60).contains(&y);
+            if x < 80 {
+                if on_block(screen) { Rgb([250, 250, 250]) } else { Rgb([120, 120, 120]) }
+            } else {
+                match webcam {
+                    Some(left) if on_block(80 + left) => Rgb([230, 200, 180]),
+                    Some(_) => Rgb([90, 80, 70]),
+                    None => Rgb([0, 0, 0]),
+                }
+            }
+        });
+        test_frame(offset_seconds, &img)
+    }
+
+    /// Screen changes over the first 5 samples, then stays still for the remaining 75 seconds
+    fn recording(webcam: impl Fn(usize) -> Option<u32>) -> Vec<Frame> {
+        (0..20)
+            .map(|i| frame(i as f64 * 5.0, if i < 4 { i as u32 * 15 } else { 60 }, webcam(i)))
+            .collect()
+    }
+
+    #[test]
+    fn test_static_screen_is_idle() {
+        let profile = analyze(&recording(|_| None), 5, None).unwrap();
+
+        assert_eq!(profile.idle_spans, vec![IdleSpan { start_seconds: 20.0, end_seconds: 95.0 }]);
+        assert_eq!(profile.idle_seconds, 75.0);
+        assert_eq!(profile.active_seconds, 25.0);
+        assert_eq!(profile.idle_ratio(), 0.75);
+        assert!(!profile.webcam_checked);
+    }
+
+    #[test]
+    fn test_webcam_movement_keeps_static_screen_active() {
+        let moving = analyze(&recording(|i| Some((i as u32 % 6) * 10)), 5, Some(WEBCAM)).unwrap();
+        assert!(moving.idle_spans.is_empty());
+        assert!(moving.webcam_checked);
+
+        let empty = analyze(&recording(|_| None), 5, Some(WEBCAM)).unwrap();
+        assert_eq!(empty.idle_seconds, 75.0);
+
+        let still = analyze(&recording(|_| Some(20)), 5, Some(WEBCAM)).unwrap();
+        assert_eq!(still.idle_seconds, 75.0);
+    }
+
+    #[test]
+    fn test_short_pauses_are_not_idle() {
+        let frames: Vec<Frame> = (0..20).map(|i| frame(i as f64 * 5.0, (i as u32 / 5) * 15, None)).collect();
+        let profile = analyze(&frames, 5, None).unwrap();
+
+        assert!(profile.idle_spans.is_empty());
+        assert_eq!(profile.active_seconds, 100.0);
+    }
+
+    #[test]
+    fn test_sample_interval_caps_sample_count() {
+        assert_eq!(sample_interval(600.0), 5);
+        assert_eq!(sample_interval(3.0 * 3600.0), 11);
+    }
+}
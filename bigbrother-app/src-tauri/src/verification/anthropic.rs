@@ -0,0 +1,498 @@
+use super::catalog::ModelCache;
+use super::error::VerificationError;
+use super::http::{HttpClient, RetryPolicy};
+use super::injection::system_prompt;
+use super::parser::{
+    check_against_request, parse_verification_response, parse_verification_value, repair_messages, repair_prompt,
+    with_original_raw,
+};
+use super::prompt::{build_prompt, frame_annotation, ResponseFormat};
+use super::provider::{endpoint, VerificationProvider, VerificationRequest, MAX_FRAMES_PER_REQUEST};
+use super::tool::{find_tool_use, forced_tool_choice, report_verification_tool, REPORT_TOOL_NAME};
+use super::usage::{TokenUsage, UsageLog};
+use crate::database::models::{ModelInfo, VerificationResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Mutex;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Tried in order when no model is pinned and the model list can't be fetched
+const FALLBACK_MODELS: &[&str] = &[
+    "claude-3-5-sonnet-20241022",
+    "claude-3-5-sonnet-20240620",
+    "claude-3-opus-20240229",
+];
+
+/// What the model sent back
+#[derive(Debug, Clone)]
+pub enum Reply {
+    ToolUse { id: String, input: serde_json::Value },
+    Text(String), // only if the model ignored the forced tool call
+}
+
+impl Reply {
+    /// Validate the reply, including that its timeline stays within the request's frames
+    pub fn parse(&self, request: &VerificationRequest) -> Result<VerificationResult, VerificationError> {
+        let (result, raw) = match self {
+            Reply::ToolUse { input, .. } => (parse_verification_value(input)?, input.to_string()),
+            Reply::Text(text) => (parse_verification_response(text)?, text.clone()),
+        };
+
+        check_against_request(result, request, &raw)
+    }
+}
+
+/// Claude via the Anthropic Messages API
+pub struct AnthropicProvider {
+    pub api_key: String,
+    pub base_url: String,
+    pub pinned_model: Option<String>,
+    http: HttpClient,
+    model_cache: Option<ModelCache>,
+    usage: UsageLog,
+    used_model: Mutex<Option<String>>,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: String, base_url: String, retry: RetryPolicy) -> Self {
+        Self {
+            api_key,
+            base_url,
+            pinned_model: None,
+            http: HttpClient::new(retry),
+            model_cache: None,
+            usage: UsageLog::default(),
+            used_model: Mutex::new(None),
+        }
+    }
+
+    /// Always use this model instead of picking the newest one, so verdicts stay comparable
+    pub fn with_pinned_model(mut self, model: Option<String>) -> Self {
+        self.pinned_model = model.filter(|m| !m.trim().is_empty());
+        self
+    }
+
+    pub fn with_model_cache(mut self, cache: Option<ModelCache>) -> Self {
+        self.model_cache = cache;
+        self
+    }
+
+    /// Query the Anthropic API for available models, newest first
+    async fn fetch_models(&self) -> Result<Vec<ModelInfo>, VerificationError> {
+        let url = endpoint(&self.base_url, "v1/models");
+        let models_json = self
+            .http
+            .send_json("Models API", |client| {
+                client
+                    .get(&url)
+                    .query(&[("limit", "1000")])
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION)
+            })
+            .await?;
+
+        let mut models: Vec<ModelInfo> = models_json["data"]
+            .as_array()
+            .ok_or_else(|| VerificationError::InvalidResponse("Invalid models response format".to_string()))?
+            .iter()
+            .filter_map(|m| {
+                Some(ModelInfo {
+                    id: m["id"].as_str()?.to_string(),
+                    display_name: m["display_name"].as_str().map(str::to_string),
+                    created_at: m["created_at"].as_str().map(str::to_string),
+                })
+            })
+            .collect();
+
+        // RFC 3339 timestamps in the same format sort chronologically
+        models.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(models)
+    }
+
+    /// Models to try in order: the pinned one, or Sonnet/Opus models newest first
+    async fn models_to_try(&self) -> Vec<String> {
+        if let Some(model) = &self.pinned_model {
+            return vec![model.clone()];
+        }
+
+        let candidates: Vec<String> = match self.list_models(false).await {
+            Ok(models) => models
+                .into_iter()
+                .map(|m| m.id)
+                .filter(|id| id.contains("claude") && (id.contains("sonnet") || id.contains("opus")))
+                .collect(),
+            Err(e) => {
+                eprintln!("Failed to query available models: {}. Using fallback list.", e);
+                vec![]
+            }
+        };
+
+        if candidates.is_empty() {
+            return FALLBACK_MODELS.iter().map(|m| m.to_string()).collect();
+        }
+
+        println!("Available Claude models: {:?}", candidates);
+        candidates
+    }
+
+    /// The prompt followed by the frames, as a single user turn
+    pub fn build_messages(&self, request: &VerificationRequest) -> serde_json::Value {
+        let mut content_parts: Vec<serde_json::Value> = vec![json!({
+            "type": "text",
+            "text": build_prompt(request, ResponseFormat::Tool)
+        })];
+
+        // Add frames (limit to avoid token limits)
+        for frame in request.frames.iter().take(MAX_FRAMES_PER_REQUEST) {
+            content_parts.push(json!({
+                "type": "text",
+                "text": frame_annotation(frame, request.recording_started_at)
+            }));
+            content_parts.push(json!({
+                "type": "image",
+                "source": {
+                    "type": "base64",
+                    "media_type": "image/jpeg",
+                    "data": frame.data
+                }
+            }));
+        }
+
+        json!([{
+            "role": "user",
+            "content": content_parts
+        }])
+    }
+
+    /// `system` keeps the app's instructions apart from the untrusted frames
+    pub fn build_request_body(&self, model: &str, system: &str, messages: &serde_json::Value) -> serde_json::Value {
+        json!({
+            "model": model,
+            "max_tokens": 2048,
+            "system": system,
+            "tools": [report_verification_tool()],
+            "tool_choice": forced_tool_choice(),
+            "messages": messages
+        })
+    }
+
+    /// Send a Messages request and return the reply, recording its token usage
+    async fn post_messages(
+        &self,
+        model: &str,
+        request: &VerificationRequest,
+        messages: &serde_json::Value,
+    ) -> Result<Reply, VerificationError> {
+        let url = endpoint(&self.base_url, "v1/messages");
+        let body = self.build_request_body(model, &system_prompt(request.canary.as_deref()), messages);
+
+        let response_json = self
+            .http
+            .send_json(&format!("Claude request to {}", model), |client| {
+                client
+                    .post(&url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION)
+                    .json(&body)
+            })
+            .await?;
+
+        self.usage.record(TokenUsage::from_anthropic(model, &response_json));
+        Self::read_reply(&response_json)
+    }
+
+    fn read_reply(response_json: &serde_json::Value) -> Result<Reply, VerificationError> {
+        if let Some(block) = find_tool_use(response_json) {
+            return Ok(Reply::ToolUse {
+                id: block["id"].as_str().unwrap_or_default().to_string(),
+                input: block["input"].clone(),
+            });
+        }
+
+        extract_text(response_json).map(|text| Reply::Text(text.to_string()))
+    }
+
+    /// Conversation asking the model to fix a reply that failed validation.
+    /// Tool calls get an error tool_result, the way the Messages API expects.
+    pub fn build_repair_messages(
+        &self,
+        request: &VerificationRequest,
+        reply: &Reply,
+        problems: &[String],
+        raw: &str,
+    ) -> serde_json::Value {
+        let prompt = build_prompt(request, ResponseFormat::Tool);
+
+        match reply {
+            Reply::ToolUse { id, input } => json!([
+                {"role": "user", "content": prompt},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": id, "name": REPORT_TOOL_NAME, "input": input}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": id, "is_error": true, "content": repair_prompt(problems)}
+                ]}
+            ]),
+            Reply::Text(_) => repair_messages(&prompt, raw, problems),
+        }
+    }
+
+    /// Give the model one chance to fix a reply that didn't validate
+    async fn repair(
+        &self,
+        model: &str,
+        request: &VerificationRequest,
+        reply: &Reply,
+        problems: &[String],
+        raw: &str,
+    ) -> Result<VerificationResult, VerificationError> {
+        eprintln!("Response from {} failed to parse ({}), requesting repair", model, problems.join("; "));
+
+        let messages = self.build_repair_messages(request, reply, problems, raw);
+
+        self.post_messages(model, request, &messages)
+            .await?
+            .parse(request)
+            .map_err(|e| with_original_raw(e, raw))
+    }
+}
+
+/// Extract text content from Claude's response
+pub fn extract_text(response_json: &serde_json::Value) -> Result<&str, VerificationError> {
+    response_json["content"][0]["text"]
+        .as_str()
+        .ok_or_else(|| VerificationError::InvalidResponse("Failed to extract text from Claude response".to_string()))
+}
+
+#[async_trait]
+impl VerificationProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn verify(&self, request: &VerificationRequest) -> Result<VerificationResult, VerificationError> {
+        let models_to_try = self.models_to_try().await;
+
+        let messages = self.build_messages(request);
+        let mut last_error = String::new();
+
+        for model in &models_to_try {
+            let reply = match self.post_messages(model, request, &messages).await {
+                Ok(reply) => reply,
+                // Only a missing model moves on; auth errors and exhausted retries would fail the same way for every model
+                Err(VerificationError::ModelNotFound(message)) => {
+                    eprintln!("{}", message);
+                    last_error = message;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            *self.used_model.lock().unwrap_or_else(|e| e.into_inner()) = Some(model.clone());
+
+            return match reply.parse(request) {
+                Err(VerificationError::ResponseParse { problems, raw }) => {
+                    self.repair(model, request, &reply, &problems, &raw).await
+                }
+                result => result,
+            };
+        }
+
+        // If we got here, none of the models exist
+        Err(VerificationError::Provider(format!("No usable Claude model found. Last error: {}", last_error)))
+    }
+
+    async fn list_models(&self, refresh: bool) -> Result<Vec<ModelInfo>, VerificationError> {
+        if !refresh {
+            if let Some(models) = self.model_cache.as_ref().and_then(|c| c.load(&self.base_url)) {
+                return Ok(models);
+            }
+        }
+
+        let models = self.fetch_models().await?;
+        if let Some(cache) = &self.model_cache {
+            if let Err(e) = cache.store(&self.base_url, &models) {
+                eprintln!("Failed to cache model list: {}", e);
+            }
+        }
+
+        Ok(models)
+    }
+
+    fn model_used(&self) -> Option<String> {
+        self.used_model.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn take_usage(&self) -> Vec<TokenUsage> {
+        self.usage.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::template::builtin_template;
+    use crate::verification::frames::Frame;
+
+    fn request_with_frames(count: usize) -> VerificationRequest {
+        VerificationRequest {
+            title: "Study calculus".to_string(),
+            description: None,
+            required_duration_minutes: 30,
+            actual_duration_minutes: 31.5,
+            frame_interval_seconds: 10,
+            frames: (0..count)
+                .map(|i| Frame {
+                    offset_seconds: i as f64 * 10.0,
+                    width: 64,
+                    height: 64,
+                    data: format!("frame{}", i),
+                    end_offset_seconds: None,
+                    tiles: Vec::new(),
+                })
+                .collect(),
+            window: None,
+            template: builtin_template(),
+            appeal: None,
+            recording_started_at: None,
+            activity: None,
+            canary: None,
+            criteria: vec![],
+        }
+    }
+
+    #[test]
+    fn test_request_body_caps_frames() {
+        let provider = AnthropicProvider::new("key".to_string(), "http://localhost".to_string(), RetryPolicy::default());
+        let messages = provider.build_messages(&request_with_frames(30));
+        let body = provider.build_request_body("claude-test", "system", &messages);
+
+        let content = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(body["model"], "claude-test");
+        assert_eq!(content.len(), 1 + 2 * MAX_FRAMES_PER_REQUEST);
+        assert!(content[0]["text"].as_str().unwrap().contains("Study calculus"));
+        assert_eq!(content[3]["text"], "Frame at 00:00:10 (10s).");
+        assert_eq!(content[4]["source"]["data"], "frame1");
+        assert_eq!(body["system"], "system");
+    }
+
+    #[test]
+    fn test_request_body_forces_report_tool() {
+        let provider = AnthropicProvider::new("key".to_string(), "http://localhost".to_string(), RetryPolicy::default());
+        let body = provider.build_request_body("claude-test", "system", &provider.build_messages(&request_with_frames(1)));
+
+        assert_eq!(body["tools"][0]["name"], "report_verification");
+        assert_eq!(body["tool_choice"]["type"], "tool");
+        assert_eq!(body["tool_choice"]["name"], "report_verification");
+    }
+
+    #[test]
+    fn test_tool_reply_validation_and_repair() {
+        let provider = AnthropicProvider::new("key".to_string(), "http://localhost".to_string(), RetryPolicy::default());
+        let reply = Reply::ToolUse {
+            id: "toolu_1".to_string(),
+            input: json!({
+                "verified": true, "confidence": 250, "time_on_task_minutes": 30,
+                "explanation": "ok", "issues": [], "timeline": []
+            }),
+        };
+
+        let Err(VerificationError::ResponseParse { problems, raw }) = reply.parse(&request_with_frames(1)) else {
+            panic!("expected out of range confidence to fail validation");
+        };
+
+        let messages = provider.build_repair_messages(&request_with_frames(1), &reply, &problems, &raw);
+        assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(messages[2]["content"][0]["is_error"], true);
+    }
+
+    #[test]
+    fn test_extract_text() {
+        let response = json!({"content": [{"type": "text", "text": "{}"}]});
+        assert_eq!(extract_text(&response).unwrap(), "{}");
+        assert!(extract_text(&json!({"content": []})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_falls_through_but_auth_error_stops() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/models")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"data": [{"id": "claude-sonnet-2"}, {"id": "claude-sonnet-1"}]}"#)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("POST", "/v1/messages")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"model": "claude-sonnet-2"}"#.to_string()))
+            .with_status(404)
+            .create_async()
+            .await;
+        let found = server
+            .mock("POST", "/v1/messages")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"model": "claude-sonnet-1"}"#.to_string()))
+            .with_body(
+                json!({
+                    "model": "claude-sonnet-1",
+                    "content": [{"type": "tool_use", "id": "toolu_1", "name": REPORT_TOOL_NAME, "input": {
+                        "verified": true, "confidence": 80, "time_on_task_minutes": 30,
+                        "explanation": "ok", "issues": [], "timeline": []
+                    }}],
+                    "usage": {"input_tokens": 100, "output_tokens": 20}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let provider = AnthropicProvider::new("key".to_string(), server.url(), RetryPolicy::default());
+        let result = provider.verify(&request_with_frames(1)).await.unwrap();
+        assert!(result.verified);
+        missing.assert_async().await;
+        found.assert_async().await;
+        assert_eq!(provider.take_usage().len(), 1);
+        assert_eq!(provider.model_used().as_deref(), Some("claude-sonnet-1"));
+
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/v1/models").match_query(mockito::Matcher::Any).with_status(401).create_async().await;
+        let rejected = server.mock("POST", "/v1/messages").with_status(401).expect(1).create_async().await;
+
+        let provider = AnthropicProvider::new("bad".to_string(), server.url(), RetryPolicy::default());
+        let result = provider.verify(&request_with_frames(1)).await;
+        assert!(matches!(result, Err(VerificationError::Unauthorized(_))));
+        rejected.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_models_ordered_by_release_and_pinned_model_skips_listing() {
+        let mut server = mockito::Server::new_async().await;
+        let listing = server
+            .mock("GET", "/v1/models")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"data": [
+                    {"id": "claude-3-5-sonnet-20241022", "created_at": "2024-10-22T00:00:00Z"},
+                    {"id": "claude-sonnet-4-20250514", "created_at": "2025-05-14T00:00:00Z"},
+                    {"id": "claude-3-5-haiku-20241022", "created_at": "2024-10-22T00:00:00Z"}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let provider = AnthropicProvider::new("key".to_string(), server.url(), RetryPolicy::default());
+        assert_eq!(
+            provider.models_to_try().await,
+            vec!["claude-sonnet-4-20250514", "claude-3-5-sonnet-20241022"]
+        );
+        listing.assert_async().await;
+
+        let pinned = AnthropicProvider::new("key".to_string(), server.url(), RetryPolicy::default())
+            .with_pinned_model(Some("claude-3-5-sonnet-20241022".to_string()));
+        assert_eq!(pinned.models_to_try().await, vec!["claude-3-5-sonnet-20241022"]);
+        listing.assert_async().await;
+    }
+}
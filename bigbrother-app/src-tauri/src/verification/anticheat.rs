@@ -0,0 +1,356 @@
+use super::activity::{is_blank, pixel_rect};
+use super::chunking::format_hms;
+use super::dedup::{dhash_gray, hamming_distance};
+use super::error::VerificationError;
+use super::frames::{decode_rgb, Frame};
+use crate::database::models::{Fingerprint, VideoRegion};
+use image::imageops;
+use image::GrayImage;
+
+/// Hashes this close are the same picture. Tighter than deduplication: replayed footage matches
+/// almost exactly, while merely similar screens shouldn't.
+const MATCH_THRESHOLD: u32 = 4;
+
+/// Shortest stretch reported as replayed
+const MIN_REPEAT_SECONDS: f64 = 60.0;
+
+/// Share of samples in a window that must match for the window to count as a repeat
+const MATCH_SHARE: f64 = 0.8;
+
+/// Share of samples in a window that must differ from the one before. A static screen repeats
+/// trivially and says nothing about replayed footage.
+const MIN_CHANGE_SHARE: f64 = 0.3;
+
+/// A live webcam's sensor noise keeps the mean brightness difference between two samples above
+/// this; a still image or frozen virtual camera stays below it
+const STILL_MEAN_DIFFERENCE: f64 = 0.3;
+
+/// Share of sample pairs that must be perfectly still before the webcam is reported
+const STILL_SHARE: f64 = 0.9;
+
+/// Fewer non-blank webcam samples than this are too few to judge
+const MIN_STILL_PAIRS: usize = 10;
+
+/// Something that suggests the recording doesn't show a real working session
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// Footage later in the recording repeats footage from earlier in it
+    RepeatedSegment { start_seconds: f64, end_seconds: f64, original_start_seconds: f64 },
+    /// Footage matches an earlier recording
+    ReplayedRecording { start_seconds: f64, end_seconds: f64, recording_id: i64, original_start_seconds: f64 },
+    /// The webcam showed the exact same picture for most of the recording
+    FrozenWebcam { still_share: f64 },
+}
+
+impl Finding {
+    /// Issue text stored on the verification
+    pub fn describe(&self) -> String {
+        match self {
+            Finding::RepeatedSegment { start_seconds, end_seconds, original_start_seconds } => format!(
+                "Repeated footage: {}-{} replays the recording from {}",
+                format_hms(*start_seconds),
+                format_hms(*end_seconds),
+                format_hms(*original_start_seconds)
+            ),
+            Finding::ReplayedRecording { start_seconds, end_seconds, recording_id, original_start_seconds } => format!(
+                "Replayed footage: {}-{} matches earlier recording #{} from {}",
+                format_hms(*start_seconds),
+                format_hms(*end_seconds),
+                recording_id,
+                format_hms(*original_start_seconds)
+            ),
+            Finding::FrozenWebcam { still_share } => format!(
+                "Frozen webcam: the webcam picture did not change at all in {:.0}% of samples",
+                still_share * 100.0
+            ),
+        }
+    }
+}
+
+/// 64-bit difference hash of the screen area, with the webcam tile blanked out so a live webcam
+/// doesn't hide replayed screen footage
+fn screen_hash(img: &GrayImage, webcam: Option<(u32, u32, u32, u32)>) -> u64 {
+    let mut screen = img.clone();
+    if let Some((x0, y0, x1, y1)) = webcam {
+        for y in y0..y1 {
+            for x in x0..x1 {
+                screen.put_pixel(x, y, image::Luma([0]));
+            }
+        }
+    }
+
+    dhash_gray(&screen)
+}
+
+fn grayscale(frames: &[Frame]) -> Result<Vec<GrayImage>, VerificationError> {
+    frames.iter().map(|frame| Ok(imageops::grayscale(&decode_rgb(frame)?))).collect()
+}
+
+/// Screen hashes of frames sampled every `interval` seconds
+pub fn fingerprint(frames: &[Frame], interval: u32, webcam: Option<VideoRegion>) -> Result<Fingerprint, VerificationError> {
+    let hashes = grayscale(frames)?
+        .iter()
+        .map(|img| screen_hash(img, webcam.map(|region| pixel_rect(&region, img.width(), img.height()))))
+        .collect();
+
+    Ok(Fingerprint { interval_seconds: interval, hashes })
+}
+
+/// Whether `window` shows enough change to be worth matching
+fn is_varied(window: &[u64]) -> bool {
+    let changes = window
+        .windows(2)
+        .filter(|pair| hamming_distance(pair[0], pair[1]) > MATCH_THRESHOLD)
+        .count();
+    changes as f64 >= MIN_CHANGE_SHARE * window.len() as f64
+}
+
+fn windows_match(a: &[u64], b: &[u64]) -> bool {
+    let allowed_misses = a.len() - (MATCH_SHARE * a.len() as f64).ceil() as usize;
+    let mut misses = 0;
+    for (x, y) in a.iter().zip(b) {
+        if hamming_distance(*x, *y) > MATCH_THRESHOLD {
+            misses += 1;
+            if misses > allowed_misses {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// A run of `len` samples of footage starting at `footage_start` that repeats the source from
+/// `source_start`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Match {
+    source_start: usize,
+    footage_start: usize,
+    len: usize,
+}
+
+/// Runs of at least `window` samples in `footage` that repeat `source`, each part of the footage
+/// reported once. With `same` set both are one recording, and only later, non-overlapping repeats
+/// count.
+fn matching_runs(source: &[u64], footage: &[u64], window: usize, same: bool) -> Vec<Match> {
+    let mut matches = Vec::new();
+    if window == 0 || source.len() < window {
+        return matches;
+    }
+
+    let mut j = 0;
+    while j + window <= footage.len() {
+        let found = (0..=source.len() - window)
+            .take_while(|&i| !same || i + window <= j)
+            .find(|&i| is_varied(&source[i..i + window]) && windows_match(&source[i..i + window], &footage[j..j + window]));
+
+        let Some(mut i) = found else {
+            j += 1;
+            continue;
+        };
+
+        // The window may tolerate a mismatch at its start; the run begins at the first real match
+        let mut len = window;
+        while hamming_distance(source[i], footage[j]) > MATCH_THRESHOLD {
+            i += 1;
+            j += 1;
+            len -= 1;
+        }
+        while i + len < source.len()
+            && j + len < footage.len()
+            && (!same || i + len < j)
+            && hamming_distance(source[i + len], footage[j + len]) <= MATCH_THRESHOLD
+        {
+            len += 1;
+        }
+
+        matches.push(Match { source_start: i, footage_start: j, len });
+        j += len;
+    }
+
+    matches
+}
+
+/// Samples in a `MIN_REPEAT_SECONDS` window at `interval`
+fn window_len(interval: u32) -> usize {
+    ((MIN_REPEAT_SECONDS / interval.max(1) as f64).ceil() as usize).max(2)
+}
+
+/// `fingerprint`'s hashes resampled to one every `interval` seconds
+fn resample(fingerprint: &Fingerprint, interval: u32) -> Vec<u64> {
+    if fingerprint.interval_seconds == interval || fingerprint.hashes.is_empty() {
+        return fingerprint.hashes.clone();
+    }
+
+    let duration = fingerprint.hashes.len() as f64 * fingerprint.interval_seconds as f64;
+    (0..(duration / interval as f64).floor() as usize)
+        .map(|k| {
+            let idx = (k as f64 * interval as f64 / fingerprint.interval_seconds as f64).round() as usize;
+            fingerprint.hashes[idx.min(fingerprint.hashes.len() - 1)]
+        })
+        .collect()
+}
+
+/// Stretches of the recording that repeat earlier parts of it
+pub fn find_repeats(fingerprint: &Fingerprint) -> Vec<Finding> {
+    let interval = fingerprint.interval_seconds as f64;
+    matching_runs(&fingerprint.hashes, &fingerprint.hashes, window_len(fingerprint.interval_seconds), true)
+        .into_iter()
+        .map(|m| Finding::RepeatedSegment {
+            start_seconds: m.footage_start as f64 * interval,
+            end_seconds: (m.footage_start + m.len) as f64 * interval,
+            original_start_seconds: m.source_start as f64 * interval,
+        })
+        .collect()
+}
+
+/// Stretches of the recording that match one of the `earlier` recordings' fingerprints
+pub fn find_replays(fingerprint: &Fingerprint, earlier: &[(i64, Fingerprint)]) -> Vec<Finding> {
+    let interval = fingerprint.interval_seconds;
+    earlier
+        .iter()
+        .flat_map(|(recording_id, other)| {
+            matching_runs(&resample(other, interval), &fingerprint.hashes, window_len(interval), false)
+                .into_iter()
+                .map(|m| Finding::ReplayedRecording {
+                    start_seconds: m.footage_start as f64 * interval as f64,
+                    end_seconds: (m.footage_start + m.len) as f64 * interval as f64,
+                    recording_id: *recording_id,
+                    original_start_seconds: m.source_start as f64 * interval as f64,
+                })
+        })
+        .collect()
+}
+
+/// A webcam that shows exactly the same non-blank picture sample after sample
+pub fn find_frozen_webcam(frames: &[Frame], webcam: VideoRegion) -> Result<Option<Finding>, VerificationError> {
+    let images = grayscale(frames)?;
+    let mut pairs = 0usize;
+    let mut still = 0usize;
+
+    for pair in images.windows(2) {
+        // A blank tile (camera off, or an empty rect) is the idle check's business
+        let (x0, y0, x1, y1) = pixel_rect(&webcam, pair[1].width(), pair[1].height());
+        if is_blank(&pair[1], (x0, y0, x1, y1)) {
+            continue;
+        }
+
+        let mut total = 0u64;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                total += u64::from(pair[1].get_pixel(x, y)[0].abs_diff(pair[0].get_pixel(x, y)[0]));
+            }
+        }
+        let mean = total as f64 / ((x1 - x0) * (y1 - y0)) as f64;
+
+        pairs += 1;
+        if mean < STILL_MEAN_DIFFERENCE {
+            still += 1;
+        }
+    }
+
+    if pairs < MIN_STILL_PAIRS {
+        return Ok(None);
+    }
+
+    let still_share = still as f64 / pairs as f64;
+    Ok((still_share >= STILL_SHARE).then_some(Finding::FrozenWebcam { still_share }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::test_frame;
+    use image::{Rgb, RgbImage};
+
+    /// Distinct hashes that differ from each other by far more than the match threshold
+    fn scene(n: u64) -> u64 {
+        n.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn fingerprint(hashes: Vec<u64>) -> Fingerprint {
+        Fingerprint { interval_seconds: 10, hashes }
+    }
+
+    #[test]
+    fn test_replayed_stretch_within_recording() {
+        // 0-90s of varied footage, a different stretch, then the first 80 seconds again
+        let mut hashes: Vec<u64> = (0..9).map(scene).collect();
+        hashes.extend((100..110).map(scene));
+        hashes.extend((0..8).map(scene));
+
+        let findings = find_repeats(&fingerprint(hashes));
+        assert_eq!(
+            findings,
+            vec![Finding::RepeatedSegment { start_seconds: 190.0, end_seconds: 270.0, original_start_seconds: 0.0 }]
+        );
+        assert_eq!(findings[0].describe(), "Repeated footage: 00:03:10-00:04:30 replays the recording from 00:00:00");
+    }
+
+    #[test]
+    fn test_static_screen_is_not_a_repeat() {
+        let hashes = vec![scene(1); 30];
+        assert!(find_repeats(&fingerprint(hashes)).is_empty());
+
+        let distinct: Vec<u64> = (0..30).map(scene).collect();
+        assert!(find_repeats(&fingerprint(distinct)).is_empty());
+    }
+
+    #[test]
+    fn test_footage_matching_earlier_recording() {
+        // The earlier recording was sampled every 5 seconds, with each scene lasting 10 seconds
+        let earlier = Fingerprint {
+            interval_seconds: 5,
+            hashes: (0..40).flat_map(|n| [scene(n), scene(n)]).collect(),
+        };
+        let mut hashes: Vec<u64> = (200..205).map(scene).collect();
+        hashes.extend((20..30).map(scene));
+
+        let findings = find_replays(&fingerprint(hashes), &[(7, earlier)]);
+        assert_eq!(
+            findings,
+            vec![Finding::ReplayedRecording {
+                start_seconds: 50.0,
+                end_seconds: 150.0,
+                recording_id: 7,
+                original_start_seconds: 200.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_match_tolerates_small_differences() {
+        let source: Vec<u64> = (0..6).map(scene).collect();
+        let mut footage = source.clone();
+        footage[2] ^= 0b111;
+        footage[4] = scene(99);
+        assert!(windows_match(&source, &footage));
+
+        footage[5] = scene(98);
+        assert!(!windows_match(&source, &footage));
+    }
+
+    /// A 160x90 frame with a mid-grey webcam tile in the right half, speckled by `seed`
+    fn webcam_frame(offset_seconds: f64, seed: u32) -> Frame {
+        let img = RgbImage::from_fn(160, 90, |x, y| {
+            let noise = (x.wrapping_mul(31) ^ y.wrapping_mul(17) ^ seed.wrapping_mul(2_654_435_761)) % 7;
+            if x < 80 { Rgb([200, 200, 200]) } else { Rgb([120 + noise as u8 * 4, 110, 100]) }
+        });
+        test_frame(offset_seconds, &img)
+    }
+
+    #[test]
+    fn test_frozen_webcam() {
+        let webcam = VideoRegion { x: 0.5, y: 0.0, width: 0.5, height: 1.0 };
+
+        let frozen: Vec<Frame> = (0..15).map(|i| webcam_frame(i as f64 * 5.0, 1)).collect();
+        assert!(matches!(
+            find_frozen_webcam(&frozen, webcam).unwrap(),
+            Some(Finding::FrozenWebcam { still_share }) if still_share == 1.0
+        ));
+
+        let live: Vec<Frame> = (0..15).map(|i| webcam_frame(i as f64 * 5.0, i)).collect();
+        assert_eq!(find_frozen_webcam(&live, webcam).unwrap(), None);
+
+        assert_eq!(find_frozen_webcam(&frozen[..5], webcam).unwrap(), None);
+    }
+}
@@ -0,0 +1,121 @@
+use super::error::VerificationError;
+use crate::database::models::VerificationSettings;
+use serde::{Deserialize, Serialize};
+
+/// Share of the monthly budget at which the UI is warned
+pub const WARNING_FRACTION: f64 = 0.8;
+
+/// Which limit a verification would break
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    Monthly,
+    PerVerification,
+}
+
+impl std::fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetLimit::Monthly => write!(f, "monthly budget"),
+            BudgetLimit::PerVerification => write!(f, "per-verification maximum"),
+        }
+    }
+}
+
+/// Payload of the budget warning event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetWarning {
+    pub month_spent_usd: f64,
+    pub monthly_budget_usd: f64,
+}
+
+/// Refuse a verification whose projected cost breaks the per-verification maximum
+/// or the rest of this month's budget
+pub fn check_budget(
+    settings: &VerificationSettings,
+    projected_usd: f64,
+    month_spent_usd: f64,
+) -> Result<(), VerificationError> {
+    if let Some(max) = settings.max_verification_cost_usd {
+        if projected_usd > max {
+            return Err(VerificationError::BudgetExceeded {
+                limit: BudgetLimit::PerVerification,
+                projected_usd,
+                remaining_usd: max,
+            });
+        }
+    }
+
+    if let Some(budget) = settings.monthly_budget_usd {
+        let remaining_usd = (budget - month_spent_usd).max(0.0);
+        if projected_usd > remaining_usd {
+            return Err(VerificationError::BudgetExceeded {
+                limit: BudgetLimit::Monthly,
+                projected_usd,
+                remaining_usd,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// The warning to send when new spend takes the month across the warning threshold
+pub fn crossed_warning(
+    settings: &VerificationSettings,
+    spent_before_usd: f64,
+    spent_after_usd: f64,
+) -> Option<BudgetWarning> {
+    let budget = settings.monthly_budget_usd.filter(|b| *b > 0.0)?;
+    let threshold = budget * WARNING_FRACTION;
+
+    (spent_before_usd < threshold && spent_after_usd >= threshold).then_some(BudgetWarning {
+        month_spent_usd: spent_after_usd,
+        monthly_budget_usd: budget,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(monthly: Option<f64>, per_verification: Option<f64>) -> VerificationSettings {
+        VerificationSettings {
+            monthly_budget_usd: monthly,
+            max_verification_cost_usd: per_verification,
+            ..VerificationSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_no_limits_allows_anything() {
+        assert!(check_budget(&settings(None, None), 1_000.0, 1_000.0).is_ok());
+    }
+
+    #[test]
+    fn test_refuses_over_limits() {
+        let limited = settings(Some(10.0), Some(0.5));
+
+        assert!(check_budget(&limited, 0.4, 9.0).is_ok());
+        assert!(matches!(
+            check_budget(&limited, 0.6, 0.0),
+            Err(VerificationError::BudgetExceeded { limit: BudgetLimit::PerVerification, .. })
+        ));
+        match check_budget(&limited, 0.4, 9.8) {
+            Err(VerificationError::BudgetExceeded { limit: BudgetLimit::Monthly, remaining_usd, .. }) => {
+                assert!((remaining_usd - 0.2).abs() < 1e-9)
+            }
+            other => panic!("expected monthly budget error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_warning_fires_once_when_crossing() {
+        let limited = settings(Some(10.0), None);
+
+        assert!(crossed_warning(&limited, 7.0, 7.9).is_none());
+        assert_eq!(crossed_warning(&limited, 7.9, 8.1).unwrap().month_spent_usd, 8.1);
+        assert!(crossed_warning(&limited, 8.1, 8.5).is_none());
+        assert!(crossed_warning(&settings(None, None), 0.0, 100.0).is_none());
+    }
+}
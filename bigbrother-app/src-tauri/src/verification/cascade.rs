@@ -0,0 +1,55 @@
+use super::error::VerificationError;
+use super::frames::{Frame, FrameExtractor};
+use super::provider::{with_model, MAX_FRAMES_PER_REQUEST};
+use crate::database::models::VerificationSettings;
+
+/// Triage frames are never taken more often than this
+const MIN_TRIAGE_INTERVAL_SECONDS: u32 = 10;
+
+/// Settings for the triage pass: the configured provider with the triage model pinned
+pub fn triage_settings(settings: &VerificationSettings) -> VerificationSettings {
+    with_model(settings, &settings.cascade.triage_model)
+}
+
+/// Interval that spreads one request's worth of frames over the whole recording
+pub fn triage_interval(duration_seconds: f64) -> u32 {
+    ((duration_seconds / MAX_FRAMES_PER_REQUEST as f64).ceil() as u32).max(MIN_TRIAGE_INTERVAL_SECONDS)
+}
+
+/// Sample the whole recording uniformly at low resolution for the triage model
+pub fn triage_frames(video_path: &str, duration_seconds: f64, max_dimension: u32) -> Result<Vec<Frame>, VerificationError> {
+    let mut extractor = FrameExtractor::new(video_path.to_string(), triage_interval(duration_seconds));
+    extractor.max_dimension = max_dimension;
+
+    let mut frames = extractor.extract()?;
+    frames.truncate(MAX_FRAMES_PER_REQUEST);
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::ProviderKind;
+
+    #[test]
+    fn test_triage_pins_model_of_configured_provider() {
+        let mut settings = VerificationSettings::default();
+        settings.cascade.triage_model = "small-vl".to_string();
+
+        settings.provider = ProviderKind::OpenaiCompatible;
+        let triage = triage_settings(&settings);
+        assert_eq!(triage.openai_model, "small-vl");
+
+        settings.provider = ProviderKind::Anthropic;
+        let triage = triage_settings(&settings);
+        assert_eq!(triage.anthropic_model.as_deref(), Some("small-vl"));
+        assert_eq!(triage.openai_model, settings.openai_model);
+    }
+
+    #[test]
+    fn test_triage_interval_covers_recording() {
+        assert_eq!(triage_interval(60.0), MIN_TRIAGE_INTERVAL_SECONDS);
+        let interval = triage_interval(4.0 * 3600.0);
+        assert!(interval as usize * MAX_FRAMES_PER_REQUEST >= 4 * 3600);
+    }
+}
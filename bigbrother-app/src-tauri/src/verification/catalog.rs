@@ -0,0 +1,87 @@
+use crate::database::models::ModelInfo;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a fetched model list is trusted
+pub const MODEL_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    base_url: String,
+    fetched_at: u64, // unix seconds
+    models: Vec<ModelInfo>,
+}
+
+/// Model list cached on disk, so verifications don't query `/models` every time
+#[derive(Debug, Clone)]
+pub struct ModelCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl ModelCache {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, ttl: MODEL_CACHE_TTL }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The cached list for `base_url`, if it is younger than the TTL
+    pub fn load(&self, base_url: &str) -> Option<Vec<ModelInfo>> {
+        let json = std::fs::read_to_string(&self.path).ok()?;
+        let cache: CacheFile = serde_json::from_str(&json).ok()?;
+
+        let age = Duration::from_secs(now_seconds().saturating_sub(cache.fetched_at));
+        (cache.base_url == base_url && age < self.ttl).then_some(cache.models)
+    }
+
+    pub fn store(&self, base_url: &str, models: &[ModelInfo]) -> std::io::Result<()> {
+        let cache = CacheFile {
+            base_url: base_url.to_string(),
+            fetched_at: now_seconds(),
+            models: models.to_vec(),
+        };
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&cache)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str) -> ModelInfo {
+        ModelInfo { id: id.to_string(), display_name: None, created_at: None }
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_expiry() {
+        let path = std::env::temp_dir().join(format!("bigbrother_models_{}.json", std::process::id()));
+        let cache = ModelCache::new(path.clone());
+
+        assert!(cache.load("https://api.anthropic.com").is_none());
+        cache.store("https://api.anthropic.com", &[model("claude-a"), model("claude-b")]).unwrap();
+
+        let models = cache.load("https://api.anthropic.com").unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].id, "claude-a");
+        assert!(cache.load("http://localhost:8080").is_none());
+        assert!(cache.clone().with_ttl(Duration::ZERO).load("https://api.anthropic.com").is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
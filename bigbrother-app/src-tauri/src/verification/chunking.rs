@@ -0,0 +1,304 @@
+use super::parser::same_criterion;
+use super::provider::MAX_FRAMES_PER_REQUEST;
+use crate::database::models::{CriterionScore, TimelineEntry, VerificationResult};
+use serde::{Deserialize, Serialize};
+
+/// A slice of the recording that is verified on its own
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub index: usize,
+    pub count: usize,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+}
+
+impl TimeWindow {
+    pub fn duration_seconds(&self) -> f64 {
+        self.end_seconds - self.start_seconds
+    }
+
+    /// Spread the per-request frame budget evenly across the window,
+    /// but never sample more often than `min_interval_seconds`
+    pub fn frame_interval_seconds(&self, min_interval_seconds: u32) -> u32 {
+        let spread = (self.duration_seconds() / MAX_FRAMES_PER_REQUEST as f64).ceil() as u32;
+        spread.max(min_interval_seconds).max(1)
+    }
+
+    /// Human readable range, e.g. "00:15:00-00:30:00"
+    pub fn label(&self) -> String {
+        format!("{}-{}", format_hms(self.start_seconds), format_hms(self.end_seconds))
+    }
+}
+
+pub fn format_hms(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as i64;
+    format!("{:02}:{:02}:{:02}", total / 3600, (total % 3600) / 60, total % 60)
+}
+
+/// Split a recording into windows of `window_seconds`.
+/// A trailing remainder shorter than half a window is folded into the previous window.
+pub fn plan_windows(total_seconds: f64, window_seconds: u32) -> Vec<TimeWindow> {
+    let window = window_seconds.max(60) as f64;
+    let mut bounds = Vec::new();
+    let mut start = 0.0;
+
+    while start < total_seconds {
+        let end = (start + window).min(total_seconds);
+        bounds.push((start, end));
+        start = end;
+    }
+
+    if bounds.len() > 1 {
+        let (last_start, last_end) = bounds[bounds.len() - 1];
+        if last_end - last_start < window / 2.0 {
+            bounds.pop();
+            if let Some(previous) = bounds.last_mut() {
+                previous.1 = last_end;
+            }
+        }
+    }
+
+    if bounds.is_empty() {
+        bounds.push((0.0, total_seconds.max(0.0)));
+    }
+
+    let count = bounds.len();
+    bounds
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start_seconds, end_seconds))| TimeWindow {
+            index,
+            count,
+            start_seconds,
+            end_seconds,
+        })
+        .collect()
+}
+
+/// Combine per-window verdicts into one result for the whole recording.
+///
+/// - `time_on_task_minutes` is the sum of each window's on-task time, capped at the window length
+/// - the recording passes if on-task time meets the requirement and most of the recording
+///   (by duration) was judged on-task
+/// - confidence is the duration-weighted mean, scaled down by how much the windows disagree
+/// - each success criterion adds up its segment scores, capped at 100, with the evidence of every segment
+pub fn merge_results(
+    windows: &[(TimeWindow, VerificationResult)],
+    required_duration_minutes: i64,
+) -> VerificationResult {
+    let total_seconds: f64 = windows.iter().map(|(w, _)| w.duration_seconds()).sum::<f64>().max(1.0);
+
+    let time_on_task_minutes: f64 = windows
+        .iter()
+        .map(|(w, r)| r.time_on_task_minutes.clamp(0.0, w.duration_seconds() / 60.0))
+        .sum();
+
+    let verified_seconds: f64 = windows
+        .iter()
+        .filter(|(_, r)| r.verified)
+        .map(|(w, _)| w.duration_seconds())
+        .sum();
+    let verified_share = verified_seconds / total_seconds;
+
+    let verified = time_on_task_minutes >= required_duration_minutes as f64 && verified_share >= 0.5;
+
+    // Share of the recording whose window agrees with the merged verdict
+    let agreement = if verified { verified_share } else { 1.0 - verified_share };
+    let mean_confidence: f64 = windows
+        .iter()
+        .map(|(w, r)| r.confidence.clamp(0, 100) as f64 * w.duration_seconds())
+        .sum::<f64>()
+        / total_seconds;
+    let confidence = (mean_confidence * agreement).round() as i64;
+
+    let mut issues = Vec::new();
+    let mut timeline: Vec<TimelineEntry> = Vec::new();
+    let mut explanations = Vec::new();
+    let disagreeing = windows.iter().filter(|(_, r)| r.verified != verified).count();
+
+    if disagreeing > 0 {
+        issues.push(format!(
+            "{} of {} segments disagree with the overall verdict",
+            disagreeing,
+            windows.len()
+        ));
+    }
+
+    for (window, result) in windows {
+        let label = window.label();
+        issues.extend(result.issues.iter().map(|issue| format!("[{}] {}", label, issue)));
+        timeline.extend(result.timeline.iter().cloned());
+        explanations.push(format!(
+            "[{}] {} ({}% confidence): {}",
+            label,
+            if result.verified { "on task" } else { "off task" },
+            result.confidence,
+            result.explanation
+        ));
+    }
+
+    VerificationResult {
+        verified,
+        confidence,
+        time_on_task_minutes,
+        explanation: format!(
+            "Verified in {} segments, {:.1} minutes on task.\n{}",
+            windows.len(),
+            time_on_task_minutes,
+            explanations.join("\n")
+        ),
+        issues,
+        timeline: {
+            timeline.sort_by(|a, b| a.start_seconds.total_cmp(&b.start_seconds));
+            timeline
+        },
+        criteria: merge_criteria(windows),
+    }
+}
+
+/// Segments score the share of a criterion they show done, so a criterion met across several
+/// segments adds up their scores, capped at 100. Scores are matched across windows by their
+/// criterion, in the order first seen.
+fn merge_criteria(windows: &[(TimeWindow, VerificationResult)]) -> Vec<CriterionScore> {
+    let mut criteria: Vec<&str> = Vec::new();
+    for score in windows.iter().flat_map(|(_, r)| &r.criteria) {
+        if !criteria.iter().any(|criterion| same_criterion(&score.criterion, criterion)) {
+            criteria.push(&score.criterion);
+        }
+    }
+
+    criteria
+        .into_iter()
+        .map(|criterion| {
+            let scored: Vec<(&TimeWindow, &CriterionScore)> = windows
+                .iter()
+                .filter_map(|(w, r)| r.criteria.iter().find(|c| same_criterion(&c.criterion, criterion)).map(|c| (w, c)))
+                .collect();
+
+            let mut evidence_seconds: Vec<f64> = scored.iter().flat_map(|(_, c)| c.evidence_seconds.iter().copied()).collect();
+            evidence_seconds.sort_by(f64::total_cmp);
+            evidence_seconds.dedup();
+
+            let notes: Vec<String> = scored
+                .iter()
+                .filter(|(_, c)| c.score > 0)
+                .map(|(w, c)| format!("[{}] {}", w.label(), c.note))
+                .collect();
+
+            CriterionScore {
+                criterion: criterion.to_string(),
+                score: scored.iter().map(|(_, c)| c.score).sum::<i64>().min(100),
+                evidence_seconds,
+                note: notes.join(" "),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::test_result;
+
+    fn result(verified: bool, confidence: i64, minutes: f64) -> VerificationResult {
+        VerificationResult {
+            time_on_task_minutes: minutes,
+            explanation: "segment".to_string(),
+            timeline: vec![TimelineEntry {
+                activity: "coding".to_string(),
+                ..TimelineEntry::default()
+            }],
+            ..test_result(verified, confidence)
+        }
+    }
+
+    #[test]
+    fn test_plan_windows_three_hours() {
+        let windows = plan_windows(3.0 * 3600.0, 15 * 60);
+
+        assert_eq!(windows.len(), 12);
+        assert!(windows.iter().all(|w| w.count == 12));
+        assert_eq!(windows[11].end_seconds, 3.0 * 3600.0);
+        assert_eq!(windows[0].frame_interval_seconds(10), 45);
+    }
+
+    #[test]
+    fn test_plan_windows_folds_short_tail() {
+        let windows = plan_windows(32.0 * 60.0, 15 * 60);
+
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[1].start_seconds, 15.0 * 60.0);
+        assert_eq!(windows[1].end_seconds, 32.0 * 60.0);
+    }
+
+    #[test]
+    fn test_short_recording_is_single_window() {
+        let windows = plan_windows(95.0, 15 * 60);
+
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].frame_interval_seconds(10), 10);
+    }
+
+    #[test]
+    fn test_merge_unanimous() {
+        let windows = plan_windows(30.0 * 60.0, 15 * 60);
+        let merged = merge_results(
+            &[(windows[0], result(true, 90, 14.0)), (windows[1], result(true, 80, 15.0))],
+            25,
+        );
+
+        assert!(merged.verified);
+        assert_eq!(merged.confidence, 85);
+        assert_eq!(merged.time_on_task_minutes, 29.0);
+        assert_eq!(merged.timeline.len(), 2);
+        assert!(merged.issues.is_empty());
+    }
+
+    #[test]
+    fn test_merge_caps_time_and_penalizes_disagreement() {
+        let windows = plan_windows(45.0 * 60.0, 15 * 60);
+        let merged = merge_results(
+            &[
+                (windows[0], result(true, 90, 40.0)),
+                (windows[1], result(true, 90, 15.0)),
+                (windows[2], result(false, 90, 2.0)),
+            ],
+            30,
+        );
+
+        assert!(merged.verified);
+        assert_eq!(merged.time_on_task_minutes, 32.0);
+        assert_eq!(merged.confidence, 60);
+        assert_eq!(merged.issues[0], "1 of 3 segments disagree with the overall verdict");
+    }
+
+    #[test]
+    fn test_merge_adds_up_criterion_scores() {
+        let windows = plan_windows(45.0 * 60.0, 15 * 60);
+        let score = |criterion: &str, score: i64, evidence: f64| CriterionScore {
+            criterion: criterion.to_string(),
+            score,
+            evidence_seconds: vec![evidence],
+            note: format!("{} solved", score / 20),
+        };
+        let segment = |criteria: Vec<CriterionScore>| VerificationResult { criteria, ..result(true, 90, 15.0) };
+
+        // 2 of 5 problems in the first segment, 3 in the second; matched by criterion, not by position
+        let merged = merge_results(
+            &[
+                (windows[0], segment(vec![score("Solved 5 problems", 40, 300.0), score("Checked the answers", 0, 310.0)])),
+                (windows[1], segment(vec![score("checked the answers.", 70, 1300.0), score("Solved 5 problems", 60, 1200.0)])),
+                (windows[2], segment(vec![score("Solved 5 problems", 0, 2000.0), score("Checked the answers", 80, 2400.0)])),
+            ],
+            25,
+        );
+
+        assert_eq!(merged.criteria.len(), 2);
+        assert_eq!(merged.criteria[0].criterion, "Solved 5 problems");
+        assert_eq!(merged.criteria[0].score, 100);
+        assert_eq!(merged.criteria[0].evidence_seconds, vec![300.0, 1200.0, 2000.0]);
+        assert_eq!(merged.criteria[0].note, "[00:00:00-00:15:00] 2 solved [00:15:00-00:30:00] 3 solved");
+        assert_eq!(merged.criteria[1].criterion, "Checked the answers");
+        assert_eq!(merged.criteria[1].score, 100); // capped
+    }
+}
@@ -0,0 +1,183 @@
+use super::chunking::TimeWindow;
+use super::provider::with_model;
+use crate::database::models::{SampleVariation, VerificationResult, VerificationSettings};
+
+/// Agreeing samples whose time on task spreads wider than this share of the median...
+const TIME_SPREAD_FRACTION: f64 = 0.25;
+
+/// ...and at least this many minutes disagree on how long the user worked
+const MIN_TIME_SPREAD_MINUTES: f64 = 5.0;
+
+/// How one consensus sample differs from the others
+#[derive(Debug, Clone)]
+pub struct SamplePlan {
+    pub settings: VerificationSettings,
+    pub shift: f64, // share of the frame interval the sampled moments are moved by
+}
+
+/// One plan per sample. Without models to vary, samples vary their frames instead.
+pub fn plan_samples(settings: &VerificationSettings) -> Vec<SamplePlan> {
+    let count = settings.consensus.samples.max(1) as usize;
+    let models = &settings.consensus.models;
+
+    (0..count)
+        .map(|index| match settings.consensus.variation {
+            SampleVariation::Models if !models.is_empty() => SamplePlan {
+                settings: with_model(settings, &models[index % models.len()]),
+                shift: 0.0,
+            },
+            _ => SamplePlan { settings: settings.clone(), shift: index as f64 / count as f64 },
+        })
+        .collect()
+}
+
+/// The window with its sampled moments moved `seconds` later, keeping the end in place
+pub fn shifted(window: &TimeWindow, seconds: f64) -> TimeWindow {
+    TimeWindow {
+        start_seconds: (window.start_seconds + seconds).min(window.end_seconds),
+        ..*window
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Combine labelled samples by majority vote. A tie counts as not verified. Confidence is the
+/// majority's mean confidence scaled by the share of samples that agree, and every dissenting
+/// sample is listed as an issue.
+pub fn combine(samples: &[(String, VerificationResult)]) -> VerificationResult {
+    let total = samples.len().max(1);
+    let votes = samples.iter().filter(|(_, result)| result.verified).count();
+    let verified = votes * 2 > total;
+
+    let (agreeing, dissenting): (Vec<_>, Vec<_>) = samples.iter().partition(|(_, result)| result.verified == verified);
+    let agreement = agreeing.len() as f64 / total as f64;
+
+    let Some((_, representative)) = agreeing.iter().copied().reduce(|best, sample| {
+        if sample.1.confidence > best.1.confidence { sample } else { best }
+    }) else {
+        return VerificationResult {
+            verified: false,
+            confidence: 0,
+            time_on_task_minutes: 0.0,
+            explanation: "No samples to combine".to_string(),
+            issues: Vec::new(),
+            timeline: Vec::new(),
+            criteria: Vec::new(),
+        };
+    };
+
+    let mean_confidence = agreeing.iter().map(|(_, r)| r.confidence as f64).sum::<f64>() / agreeing.len() as f64;
+    let minutes: Vec<f64> = agreeing.iter().map(|(_, r)| r.time_on_task_minutes).collect();
+    let time_on_task_minutes = median(minutes.clone());
+
+    let mut issues: Vec<String> = Vec::new();
+    for issue in agreeing.iter().flat_map(|(_, r)| &r.issues) {
+        if !issues.contains(issue) {
+            issues.push(issue.clone());
+        }
+    }
+    for (label, result) in &dissenting {
+        issues.push(format!(
+            "Samples disagree: {} found the task {} with {}% confidence: {}",
+            label,
+            if result.verified { "verified" } else { "not verified" },
+            result.confidence,
+            result.explanation
+        ));
+    }
+
+    let (low, high) = minutes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), m| (lo.min(*m), hi.max(*m)));
+    if high - low > (time_on_task_minutes * TIME_SPREAD_FRACTION).max(MIN_TIME_SPREAD_MINUTES) {
+        issues.push(format!("Samples disagree on time on task: {:.0} to {:.0} minutes", low, high));
+    }
+
+    VerificationResult {
+        verified,
+        confidence: (mean_confidence * agreement).round() as i64,
+        time_on_task_minutes,
+        explanation: format!("{} of {} samples agree. {}", agreeing.len(), total, representative.explanation),
+        issues,
+        timeline: representative.timeline.clone(),
+        criteria: representative.criteria.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::ProviderKind;
+    use crate::verification::test_support::test_result;
+
+    fn sample(label: &str, verified: bool, confidence: i64, minutes: f64) -> (String, VerificationResult) {
+        (
+            label.to_string(),
+            VerificationResult {
+                time_on_task_minutes: minutes,
+                explanation: format!("{} explanation", label),
+                issues: vec!["Phone visible at 00:10:00".to_string()],
+                ..test_result(verified, confidence)
+            },
+        )
+    }
+
+    #[test]
+    fn test_majority_with_agreement_weighted_confidence() {
+        let combined = combine(&[
+            sample("Sample 1", true, 90, 30.0),
+            sample("Sample 2", false, 70, 5.0),
+            sample("Sample 3", true, 80, 32.0),
+        ]);
+
+        assert!(combined.verified);
+        assert_eq!(combined.confidence, 57); // 85 * 2/3
+        assert_eq!(combined.time_on_task_minutes, 31.0);
+        assert_eq!(combined.explanation, "2 of 3 samples agree. Sample 1 explanation");
+        assert_eq!(
+            combined.issues,
+            vec![
+                "Phone visible at 00:10:00".to_string(),
+                "Samples disagree: Sample 2 found the task not verified with 70% confidence: Sample 2 explanation".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tie_is_not_verified_and_unanimity_keeps_confidence() {
+        let tie = combine(&[sample("Sample 1", true, 90, 30.0), sample("Sample 2", false, 60, 10.0)]);
+        assert!(!tie.verified);
+        assert_eq!(tie.confidence, 30);
+        assert_eq!(tie.issues.len(), 2);
+
+        let unanimous = combine(&[sample("Sample 1", true, 90, 30.0), sample("Sample 2", true, 80, 45.0)]);
+        assert!(unanimous.verified);
+        assert_eq!(unanimous.confidence, 85);
+        assert_eq!(unanimous.issues.last().unwrap(), "Samples disagree on time on task: 30 to 45 minutes");
+    }
+
+    #[test]
+    fn test_sample_plans() {
+        let mut settings = VerificationSettings::default();
+        settings.consensus.samples = 3;
+
+        let shifts: Vec<f64> = plan_samples(&settings).iter().map(|plan| plan.shift).collect();
+        assert_eq!(shifts, vec![0.0, 1.0 / 3.0, 2.0 / 3.0]);
+
+        settings.provider = ProviderKind::OpenaiCompatible;
+        settings.consensus.variation = SampleVariation::Models;
+        settings.consensus.models = vec!["llava".to_string(), "qwen2-vl".to_string()];
+        let models: Vec<String> = plan_samples(&settings).into_iter().map(|plan| plan.settings.openai_model).collect();
+        assert_eq!(models, vec!["llava", "qwen2-vl", "llava"]);
+
+        let window = TimeWindow { index: 0, count: 1, start_seconds: 0.0, end_seconds: 600.0 };
+        assert_eq!(shifted(&window, 10.0).start_seconds, 10.0);
+        assert_eq!(shifted(&window, 10.0).end_seconds, 600.0);
+    }
+}
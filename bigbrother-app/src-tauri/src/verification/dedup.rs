@@ -0,0 +1,165 @@
+use super::error::VerificationError;
+use super::frames::Frame;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::imageops::{self, FilterType};
+use image::GrayImage;
+
+/// Frames within this many differing hash bits are treated as the same screen
+pub const DEFAULT_HASH_THRESHOLD: u32 = 6;
+
+/// Frames are extracted this many times more densely than the request budget allows,
+/// so the budget freed by collapsing duplicates can go to moments where the screen changed
+pub const OVERSAMPLE_FACTOR: u32 = 3;
+
+#[derive(Debug)]
+pub struct DedupOutcome {
+    pub frames: Vec<Frame>,
+    pub dropped: usize,
+}
+
+/// 64-bit difference hash of an encoded frame, see `dhash_gray`
+pub fn dhash(frame: &Frame) -> Result<u64, VerificationError> {
+    let bytes = STANDARD
+        .decode(&frame.data)
+        .map_err(|e| VerificationError::InvalidResponse(format!("Frame is not valid base64: {}", e)))?;
+
+    Ok(dhash_gray(&image::load_from_memory(&bytes)?.to_luma8()))
+}
+
+/// 64-bit difference hash: shrink to 9x8 and record whether each pixel is brighter than its
+/// right-hand neighbour
+pub fn dhash_gray(img: &GrayImage) -> u64 {
+    let small = imageops::resize(img, 9, 8, FilterType::Triangle);
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+
+    hash
+}
+
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+struct Run {
+    frame: Frame,
+    hash: u64,
+    change: u32, // distance from the previous run, u32::MAX for the first
+    end_offset_seconds: f64,
+    len: usize,
+}
+
+/// Collapse runs of near-identical frames into one representative annotated with the span it
+/// covers. If more distinct moments remain than `budget`, keep the ones with the biggest visual
+/// change from the moment before them.
+pub fn deduplicate(frames: Vec<Frame>, threshold: u32, budget: usize) -> Result<DedupOutcome, VerificationError> {
+    let total = frames.len();
+    let mut runs: Vec<Run> = Vec::new();
+
+    for frame in frames {
+        let hash = dhash(&frame)?;
+
+        match runs.last_mut() {
+            Some(run) if hamming_distance(run.hash, hash) <= threshold => {
+                run.end_offset_seconds = frame.offset_seconds;
+                run.len += 1;
+            }
+            previous => {
+                let change = previous.map_or(u32::MAX, |run| hamming_distance(run.hash, hash));
+                runs.push(Run {
+                    end_offset_seconds: frame.offset_seconds,
+                    frame,
+                    hash,
+                    change,
+                    len: 1,
+                });
+            }
+        }
+    }
+
+    if runs.len() > budget {
+        // Rank by change, keep the top `budget`, then restore chronological order
+        let mut ranked: Vec<usize> = (0..runs.len()).collect();
+        ranked.sort_by(|&a, &b| runs[b].change.cmp(&runs[a].change).then(a.cmp(&b)));
+        ranked.truncate(budget);
+        ranked.sort_unstable();
+
+        let mut keep = ranked.into_iter().peekable();
+        runs = runs
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| keep.next_if_eq(idx).is_some())
+            .map(|(_, run)| run)
+            .collect();
+    }
+
+    let frames: Vec<Frame> = runs
+        .into_iter()
+        .map(|run| {
+            let mut frame = run.frame;
+            if run.len > 1 {
+                frame.end_offset_seconds = Some(run.end_offset_seconds);
+            }
+            frame
+        })
+        .collect();
+
+    Ok(DedupOutcome {
+        dropped: total - frames.len(),
+        frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::edge_frame;
+
+    #[test]
+    fn test_identical_frames_hash_equal() {
+        assert_eq!(dhash(&edge_frame(0.0, 50)).unwrap(), dhash(&edge_frame(10.0, 50)).unwrap());
+        assert!(hamming_distance(dhash(&edge_frame(0.0, 20)).unwrap(), dhash(&edge_frame(0.0, 140)).unwrap()) > DEFAULT_HASH_THRESHOLD);
+    }
+
+    #[test]
+    fn test_collapses_runs_with_span() {
+        let frames = vec![
+            edge_frame(0.0, 20),
+            edge_frame(10.0, 20),
+            edge_frame(20.0, 20),
+            edge_frame(30.0, 140),
+            edge_frame(40.0, 20),
+        ];
+
+        let outcome = deduplicate(frames, DEFAULT_HASH_THRESHOLD, 20).unwrap();
+
+        assert_eq!(outcome.dropped, 2);
+        assert_eq!(outcome.frames.len(), 3);
+        assert_eq!(outcome.frames[0].end_offset_seconds, Some(20.0));
+        assert_eq!(outcome.frames[1].offset_seconds, 30.0);
+        assert_eq!(outcome.frames[1].end_offset_seconds, None);
+    }
+
+    #[test]
+    fn test_budget_keeps_biggest_changes_in_order() {
+        let frames = vec![
+            edge_frame(0.0, 20),
+            edge_frame(10.0, 60),
+            edge_frame(20.0, 140),
+            edge_frame(30.0, 120),
+        ];
+
+        let outcome = deduplicate(frames, 0, 2).unwrap();
+
+        assert_eq!(outcome.frames.len(), 2);
+        assert_eq!(outcome.dropped, 2);
+        assert_eq!(outcome.frames[0].offset_seconds, 0.0);
+        assert!(outcome.frames[1].offset_seconds > 0.0);
+    }
+}
@@ -0,0 +1,51 @@
+use super::budget::BudgetLimit;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum VerificationError {
+    #[error("FFmpeg was not found. Install FFmpeg and make sure it is on your PATH.")]
+    FfmpegNotFound,
+
+    #[error("Video file not found: {0}")]
+    VideoNotFound(String),
+
+    #[error("Video file is corrupt or unreadable: {0}")]
+    CorruptVideo(String),
+
+    #[error("FFmpeg failed: {0}")]
+    Ffmpeg(String),
+
+    #[error("Recording is {actual_seconds:.0} seconds long but the task requires {required_seconds} seconds")]
+    RecordingTooShort { actual_seconds: f64, required_seconds: i64 },
+
+    #[error("Claude API key not set. Please configure it in settings.")]
+    MissingApiKey,
+
+    #[error("{0}")]
+    Provider(String),
+
+    #[error("The API key was rejected: {0}")]
+    Unauthorized(String),
+
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
+
+    #[error("Service unavailable after {attempts} attempts: {message}")]
+    Unavailable { attempts: u32, message: String },
+
+    #[error("Invalid response from model: {0}")]
+    InvalidResponse(String),
+
+    /// The model replied, but not with a usable verification result. `raw` keeps the reply for debugging.
+    #[error("Could not parse the model's verification: {}", problems.join("; "))]
+    ResponseParse { problems: Vec<String>, raw: String },
+
+    #[error("Verification would cost about ${projected_usd:.2}, over the {limit} (${remaining_usd:.2} available)")]
+    BudgetExceeded { limit: BudgetLimit, projected_usd: f64, remaining_usd: f64 },
+
+    #[error("Failed to process frame: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,410 @@
+use super::error::VerificationError;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration, Local};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::RgbImage;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Longest edge the vision API accepts without downscaling it server-side
+pub const MAX_FRAME_DIMENSION: u32 = 1568;
+
+const JPEG_QUALITY: u8 = 80;
+
+/// A single frame pulled out of a recording, ready to send to the vision API
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub offset_seconds: f64, // position in the recording
+    pub width: u32,
+    pub height: u32,
+    pub data: String, // base64 encoded JPEG
+    pub end_offset_seconds: Option<f64>, // set when this frame stands in for near-identical frames up to this offset
+    pub tiles: Vec<f64>, // offsets of the frames tiled into a mosaic, in reading order; empty for a single frame
+}
+
+impl Frame {
+    /// Offset of the latest moment this image shows
+    pub fn last_offset_seconds(&self) -> f64 {
+        self.end_offset_seconds
+            .or(self.tiles.last().copied())
+            .unwrap_or(self.offset_seconds)
+    }
+
+    /// Wall-clock time the frame was captured, given when the recording started
+    pub fn captured_at(&self, recording_started_at: Option<DateTime<Local>>) -> Option<DateTime<Local>> {
+        recording_started_at.map(|start| start + Duration::milliseconds((self.offset_seconds * 1000.0).round() as i64))
+    }
+}
+
+pub struct FrameExtractor {
+    pub video_path: String,
+    pub interval_seconds: u32,
+    pub max_dimension: u32,
+    pub start_seconds: f64,
+    pub duration_seconds: Option<f64>,
+}
+
+impl FrameExtractor {
+    pub fn new(video_path: String, interval_seconds: u32) -> Self {
+        Self {
+            video_path,
+            interval_seconds,
+            max_dimension: MAX_FRAME_DIMENSION,
+            start_seconds: 0.0,
+            duration_seconds: None,
+        }
+    }
+
+    /// Only extract frames from `duration_seconds` starting at `start_seconds`
+    pub fn with_window(mut self, start_seconds: f64, duration_seconds: f64) -> Self {
+        self.start_seconds = start_seconds;
+        self.duration_seconds = Some(duration_seconds);
+        self
+    }
+
+    /// Extract one frame every `interval_seconds` using FFmpeg
+    /// Frames are written to a scratch directory which is removed afterwards
+    pub fn extract(&self) -> Result<Vec<Frame>, VerificationError> {
+        let interval = self.interval_seconds.max(1);
+        let scratch = ScratchDir::new()?;
+
+        println!(
+            "Extracting frames from {} every {} seconds into {}",
+            self.video_path,
+            interval,
+            scratch.path.display()
+        );
+
+        let frame_files = self.run_ffmpeg(&scratch, &format!("fps=1/{}", interval), &[])?;
+
+        let mut frames = Vec::with_capacity(frame_files.len());
+        for (idx, file) in frame_files.iter().enumerate() {
+            let mut frame = encode_frame(file, self.max_dimension)?;
+            frame.offset_seconds = self.start_seconds + (idx as u32 * interval) as f64;
+            frames.push(frame);
+        }
+
+        println!("Extracted {} frames", frames.len());
+
+        Ok(frames)
+    }
+
+    /// Extract the first frame plus every frame whose FFmpeg scene score exceeds `threshold`
+    /// (0-1, higher means a bigger visual change), each paired with its score
+    pub fn extract_scene_changes(&self, threshold: f64) -> Result<Vec<(Frame, f64)>, VerificationError> {
+        let scratch = ScratchDir::new()?;
+
+        println!(
+            "Extracting scene changes above {:.2} from {} into {}",
+            threshold,
+            self.video_path,
+            scratch.path.display()
+        );
+
+        // The metadata file name is relative to the scratch directory so no path needs escaping
+        // inside the filter graph
+        let filter = format!(
+            "select='eq(n\\,0)+gt(scene\\,{:.3})',metadata=print:file={}",
+            threshold, SCENE_METADATA_FILE
+        );
+        let frame_files = self.run_ffmpeg(&scratch, &filter, &["-vsync", "vfr"])?;
+        let scenes = parse_scene_metadata(&std::fs::read_to_string(scratch.path.join(SCENE_METADATA_FILE))?);
+
+        if scenes.len() != frame_files.len() {
+            return Err(VerificationError::Ffmpeg(format!(
+                "scene detection wrote {} frames but {} timestamps",
+                frame_files.len(),
+                scenes.len()
+            )));
+        }
+
+        let mut frames = Vec::with_capacity(frame_files.len());
+        for (file, (pts_time, score)) in frame_files.iter().zip(scenes) {
+            let mut frame = encode_frame(file, self.max_dimension)?;
+            frame.offset_seconds = self.start_seconds + pts_time;
+            frames.push((frame, score));
+        }
+
+        println!("Extracted {} scene-change frames", frames.len());
+
+        Ok(frames)
+    }
+
+    /// Run FFmpeg over the configured window with the video filter `filter`, writing JPEGs into
+    /// `scratch`, and return them in order
+    fn run_ffmpeg(&self, scratch: &ScratchDir, filter: &str, extra_args: &[&str]) -> Result<Vec<PathBuf>, VerificationError> {
+        let video_path = Path::new(&self.video_path);
+        if !video_path.is_file() {
+            return Err(VerificationError::VideoNotFound(self.video_path.clone()));
+        }
+        // FFmpeg runs inside the scratch directory, so the input must not be relative
+        let video_path = if video_path.is_absolute() {
+            video_path.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(video_path)
+        };
+
+        let mut cmd = Command::new("ffmpeg");
+        cmd.current_dir(&scratch.path);
+        cmd.args(["-v", "error", "-y"]);
+
+        // Input seeking keeps long recordings fast; output timestamps restart at zero
+        if self.start_seconds > 0.0 {
+            cmd.arg("-ss").arg(format!("{:.3}", self.start_seconds));
+        }
+        if let Some(duration) = self.duration_seconds {
+            cmd.arg("-t").arg(format!("{:.3}", duration));
+        }
+
+        let output = cmd
+            .arg("-i")
+            .arg(&video_path)
+            .arg("-vf")
+            .arg(filter)
+            .args(extra_args)
+            .args(["-q:v", "2"])
+            .arg(scratch.path.join("frame_%05d.jpg"))
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => VerificationError::FfmpegNotFound,
+                _ => VerificationError::Io(e),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(classify_ffmpeg_error(&self.video_path, stderr));
+        }
+
+        let mut frame_files: Vec<PathBuf> = std::fs::read_dir(&scratch.path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jpg"))
+            .collect();
+        frame_files.sort();
+
+        if frame_files.is_empty() {
+            return Err(VerificationError::CorruptVideo(format!(
+                "{} (no frames could be decoded)",
+                self.video_path
+            )));
+        }
+
+        Ok(frame_files)
+    }
+}
+
+/// Written by the `metadata=print` filter during scene detection
+const SCENE_METADATA_FILE: &str = "scenes.txt";
+
+/// `(pts_time, scene score)` per selected frame from `metadata=print` output, which has a
+/// `frame:N pts:P pts_time:T` line followed by that frame's `key=value` lines. The first frame
+/// has no scene score and gets 0.
+pub fn parse_scene_metadata(text: &str) -> Vec<(f64, f64)> {
+    let mut scenes: Vec<(f64, f64)> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(pts_time) = line
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix("pts_time:"))
+            .and_then(|t| t.parse().ok())
+        {
+            scenes.push((pts_time, 0.0));
+        } else if let Some(score) = line.strip_prefix("lavfi.scene_score=").and_then(|s| s.parse().ok()) {
+            if let Some(last) = scenes.last_mut() {
+                last.1 = score;
+            }
+        }
+    }
+
+    scenes
+}
+
+fn classify_ffmpeg_error(video_path: &str, stderr: String) -> VerificationError {
+    let lower = stderr.to_lowercase();
+    let corrupt = lower.contains("invalid data found")
+        || lower.contains("moov atom not found")
+        || lower.contains("could not find codec parameters")
+        || lower.contains("end of file");
+
+    if corrupt {
+        VerificationError::CorruptVideo(format!("{}: {}", video_path, stderr))
+    } else {
+        VerificationError::Ffmpeg(stderr)
+    }
+}
+
+/// Load a frame from disk, shrink it to fit `max_dimension` and encode as base64 JPEG
+fn encode_frame(path: &Path, max_dimension: u32) -> Result<Frame, VerificationError> {
+    let mut img = image::open(path)?;
+
+    if img.width() > max_dimension || img.height() > max_dimension {
+        img = img.resize(max_dimension, max_dimension, FilterType::Triangle);
+    }
+
+    let rgb = img.to_rgb8();
+
+    Ok(Frame {
+        offset_seconds: 0.0,
+        width: rgb.width(),
+        height: rgb.height(),
+        data: encode_jpeg(&rgb)?,
+        end_offset_seconds: None,
+        tiles: Vec::new(),
+    })
+}
+
+/// Decode a frame's base64 JPEG back into pixels
+pub(crate) fn decode_rgb(frame: &Frame) -> Result<RgbImage, VerificationError> {
+    let bytes = STANDARD
+        .decode(&frame.data)
+        .map_err(|e| VerificationError::InvalidResponse(format!("Frame is not valid base64: {}", e)))?;
+    Ok(image::load_from_memory(&bytes)?.to_rgb8())
+}
+
+/// Encode an image the way frames are sent: base64 JPEG
+pub(crate) fn encode_jpeg(rgb: &RgbImage) -> Result<String, VerificationError> {
+    let mut buffer = Vec::new();
+    JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY).encode_image(rgb)?;
+    Ok(STANDARD.encode(&buffer))
+}
+
+/// Temporary directory that is deleted when dropped
+struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    fn new() -> Result<Self, VerificationError> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "bigbrother_frames_{}_{}_{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path)?;
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::{ffmpeg_available, test_clip};
+
+    #[test]
+    fn test_extracts_frames_at_interval() {
+        let Some(clip) = test_clip(10, "320x240") else { return };
+
+        let frames = FrameExtractor::new(clip.to_string_lossy().to_string(), 2)
+            .extract()
+            .unwrap();
+        let _ = std::fs::remove_file(&clip);
+
+        assert!((4..=6).contains(&frames.len()), "got {} frames", frames.len());
+        assert_eq!(frames[1].offset_seconds, 2.0);
+        for frame in &frames {
+            let bytes = STANDARD.decode(&frame.data).unwrap();
+            let img = image::load_from_memory(&bytes).unwrap();
+            assert_eq!((img.width(), img.height()), (320, 240));
+        }
+    }
+
+    #[test]
+    fn test_downscales_large_frames() {
+        let Some(clip) = test_clip(2, "3200x1800") else { return };
+
+        let frames = FrameExtractor::new(clip.to_string_lossy().to_string(), 1)
+            .extract()
+            .unwrap();
+        let _ = std::fs::remove_file(&clip);
+
+        assert!(!frames.is_empty());
+        for frame in &frames {
+            assert_eq!(frame.width, MAX_FRAME_DIMENSION);
+            assert_eq!(frame.height, 882);
+        }
+    }
+
+    #[test]
+    fn test_extracts_window() {
+        let Some(clip) = test_clip(12, "160x90") else { return };
+
+        let frames = FrameExtractor::new(clip.to_string_lossy().to_string(), 2)
+            .with_window(6.0, 4.0)
+            .extract()
+            .unwrap();
+        let _ = std::fs::remove_file(&clip);
+
+        assert!((2..=3).contains(&frames.len()), "got {} frames", frames.len());
+        assert_eq!(frames[0].offset_seconds, 6.0);
+        assert_eq!(frames[1].offset_seconds, 8.0);
+    }
+
+    #[test]
+    fn test_parse_scene_metadata() {
+        let text = "frame:0    pts:0       pts_time:0\n\
+                    frame:1    pts:61440   pts_time:4.8\n\
+                    lavfi.scene_score=0.512\n\
+                    frame:2    pts:150016  pts_time:11.72\n\
+                    lavfi.scene_score=0.34\n";
+
+        assert_eq!(parse_scene_metadata(text), vec![(0.0, 0.0), (4.8, 0.512), (11.72, 0.34)]);
+        assert!(parse_scene_metadata("").is_empty());
+    }
+
+    #[test]
+    fn test_scene_changes_include_first_frame() {
+        let Some(clip) = test_clip(6, "160x90") else { return };
+
+        let frames = FrameExtractor::new(clip.to_string_lossy().to_string(), 1)
+            .with_window(2.0, 3.0)
+            .extract_scene_changes(0.3)
+            .unwrap();
+        let _ = std::fs::remove_file(&clip);
+
+        assert!(!frames.is_empty());
+        assert_eq!(frames[0].0.offset_seconds, 2.0);
+        assert!(frames.windows(2).all(|pair| pair[0].0.offset_seconds < pair[1].0.offset_seconds));
+    }
+
+    #[test]
+    fn test_missing_video() {
+        let result = FrameExtractor::new("/nonexistent/video.mp4".to_string(), 10).extract();
+        assert!(matches!(result, Err(VerificationError::VideoNotFound(_))));
+    }
+
+    #[test]
+    fn test_corrupt_video() {
+        if !ffmpeg_available() {
+            return;
+        }
+
+        let path = std::env::temp_dir().join(format!("bigbrother_corrupt_{}.mp4", std::process::id()));
+        std::fs::write(&path, b"this is not a video").unwrap();
+
+        let result = FrameExtractor::new(path.to_string_lossy().to_string(), 10).extract();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(result, Err(VerificationError::CorruptVideo(_))));
+    }
+
+    #[test]
+    fn test_scratch_dir_removed_on_drop() {
+        let scratch = ScratchDir::new().unwrap();
+        let path = scratch.path.clone();
+        assert!(path.is_dir());
+
+        drop(scratch);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,249 @@
+use super::error::VerificationError;
+use crate::database::models::VerificationSettings;
+use reqwest::{header::HeaderMap, RequestBuilder, Response, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How the client retries transient failures
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,     // cap for computed backoff and for Retry-After
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(180), // vision requests with 20 frames can be slow
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_settings(settings: &VerificationSettings) -> Self {
+        Self {
+            max_attempts: settings.max_retries.saturating_add(1),
+            request_timeout: Duration::from_secs(settings.request_timeout_seconds.max(1)),
+            ..Self::default()
+        }
+    }
+
+    /// Exponential backoff with jitter: `base * 2^retry`, capped, then scaled by a random 50-100%
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self.base_delay.saturating_mul(2u32.saturating_pow(retry));
+        exponential.min(self.max_delay).mul_f64(0.5 + jitter() * 0.5)
+    }
+}
+
+/// Uniform random number in [0, 1) without pulling in a RNG crate
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parse a `Retry-After` header given either as seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds >= 0.0).then(|| Duration::from_secs_f64(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Worth retrying: rate limits, overload and server errors
+fn is_transient(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504 | 529)
+}
+
+/// Sends requests with timeouts, retrying transient failures and classifying the rest
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+impl HttpClient {
+    pub fn new(policy: RetryPolicy) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(policy.request_timeout)
+            .connect_timeout(Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+
+        Self { client, policy }
+    }
+
+    /// Send a request built by `build` (called again for every attempt) and return the JSON body.
+    ///
+    /// - network errors, timeouts, 429, 529 and 5xx are retried with backoff, honouring Retry-After
+    /// - 401/403 fail at once with `Unauthorized`
+    /// - 404 fails at once with `ModelNotFound`, so callers can move on to another model
+    /// - any other error status fails at once with `Provider`
+    pub async fn send_json<F>(&self, what: &str, build: F) -> Result<serde_json::Value, VerificationError>
+    where
+        F: Fn(&reqwest::Client) -> RequestBuilder,
+    {
+        let mut last_error = String::new();
+
+        for attempt in 0..self.policy.max_attempts.max(1) {
+            if attempt > 0 {
+                eprintln!("{} failed ({}), retrying (attempt {})", what, last_error, attempt + 1);
+            }
+
+            let delay = match build(&self.client).send().await {
+                Ok(response) if response.status().is_success() => return Self::read_json(what, response).await,
+                Ok(response) => {
+                    let status = response.status();
+                    let server_delay = retry_after(response.headers());
+                    let body = response.text().await.unwrap_or_default();
+
+                    match status {
+                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                            return Err(VerificationError::Unauthorized(format!("{} was rejected ({}): {}", what, status, body)))
+                        }
+                        StatusCode::NOT_FOUND => {
+                            return Err(VerificationError::ModelNotFound(format!("{} ({}): {}", what, status, body)))
+                        }
+                        status if !is_transient(status) => {
+                            return Err(VerificationError::Provider(format!("{} failed ({}): {}", what, status, body)))
+                        }
+                        _ => {}
+                    }
+
+                    last_error = format!("{}: {}", status, body);
+                    server_delay
+                }
+                Err(e) => {
+                    last_error = if e.is_timeout() { format!("timed out: {}", e) } else { e.to_string() };
+                    None
+                }
+            };
+
+            if attempt + 1 < self.policy.max_attempts {
+                let wait = delay.unwrap_or_else(|| self.policy.backoff(attempt)).min(self.policy.max_delay);
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        Err(VerificationError::Unavailable {
+            attempts: self.policy.max_attempts.max(1),
+            message: format!("{}: {}", what, last_error),
+        })
+    }
+
+    async fn read_json(what: &str, response: Response) -> Result<serde_json::Value, VerificationError> {
+        response
+            .json()
+            .await
+            .map_err(|e| VerificationError::InvalidResponse(format!("Failed to parse {} response: {}", what, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+            request_timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::default();
+
+        for retry in 0..10 {
+            let delay = policy.backoff(retry);
+            let ceiling = policy.base_delay.saturating_mul(2u32.pow(retry)).min(policy.max_delay);
+            assert!(delay <= ceiling && delay >= ceiling / 2, "retry {}: {:?}", retry, delay);
+        }
+    }
+
+    #[test]
+    fn test_retry_after_formats() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert("retry-after", "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limit_until_exhausted() {
+        let mut server = mockito::Server::new_async().await;
+        let limited = server
+            .mock("POST", "/v1/messages")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = HttpClient::new(fast_policy());
+        let url = format!("{}/v1/messages", server.url());
+        let result = client.send_json("Claude request", |c| c.post(&url)).await;
+        assert!(matches!(result, Err(VerificationError::Unavailable { attempts: 3, .. })));
+        limited.assert_async().await;
+        limited.remove_async().await;
+
+        let ok = server
+            .mock("POST", "/v1/messages")
+            .with_status(200)
+            .with_body(r#"{"ok": true}"#)
+            .create_async()
+            .await;
+        let value = client.send_json("Claude request", |c| c.post(&url)).await.unwrap();
+        assert_eq!(value["ok"], true);
+        ok.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_classifies_auth_and_missing_model_without_retrying() {
+        let mut server = mockito::Server::new_async().await;
+        let unauthorized = server.mock("POST", "/auth").with_status(401).expect(1).create_async().await;
+        let missing = server.mock("POST", "/model").with_status(404).expect(1).create_async().await;
+        let bad_request = server.mock("POST", "/bad").with_status(400).expect(1).create_async().await;
+
+        let client = HttpClient::new(fast_policy());
+        let send = |path: &str| {
+            let url = format!("{}{}", server.url(), path);
+            let client = client.clone();
+            async move { client.send_json("request", |c| c.post(&url)).await }
+        };
+
+        assert!(matches!(send("/auth").await, Err(VerificationError::Unauthorized(_))));
+        assert!(matches!(send("/model").await, Err(VerificationError::ModelNotFound(_))));
+        assert!(matches!(send("/bad").await, Err(VerificationError::Provider(_))));
+
+        unauthorized.assert_async().await;
+        missing.assert_async().await;
+        bad_request.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_network_error_is_retried() {
+        // Nothing listens on this port
+        let client = HttpClient::new(fast_policy());
+        let result = client.send_json("request", |c| c.get("http://127.0.0.1:9/")).await;
+        assert!(matches!(result, Err(VerificationError::Unavailable { attempts: 3, .. })));
+    }
+}
@@ -0,0 +1,38 @@
+pub mod activity;
+pub mod anticheat;
+pub mod anthropic;
+pub mod budget;
+pub mod cascade;
+pub mod catalog;
+pub mod chunking;
+pub mod consensus;
+pub mod dedup;
+pub mod error;
+pub mod frames;
+pub mod http;
+pub mod injection;
+pub mod mock;
+pub mod mosaic;
+pub mod openai;
+pub mod parser;
+pub mod policy;
+pub mod pricing;
+pub mod probe;
+pub mod prompt;
+pub mod provider;
+pub mod sampling;
+pub mod stamp;
+pub mod template;
+pub mod tool;
+pub mod usage;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
+pub use chunking::{merge_results, plan_windows, TimeWindow};
+pub use dedup::{deduplicate, DedupOutcome};
+pub use error::VerificationError;
+pub use frames::{Frame, FrameExtractor};
+pub use probe::{check_minimum_duration, probe_duration_seconds};
+pub use provider::{build_provider, Appeal, VerificationProvider, VerificationRequest};
+pub use usage::TokenUsage;
@@ -0,0 +1,181 @@
+use super::dedup::{dhash, hamming_distance};
+use super::error::VerificationError;
+use super::frames::{decode_rgb, encode_jpeg, Frame, MAX_FRAME_DIMENSION};
+use super::sampling::spread;
+use super::stamp::{draw_label, frame_label};
+use crate::database::models::FrameMode;
+use chrono::{DateTime, Local};
+use image::imageops::{self, FilterType};
+use image::RgbImage;
+
+pub const MOSAIC_COLUMNS: usize = 3;
+
+/// Frames per mosaic: a 3x3 grid
+pub const TILES_PER_MOSAIC: usize = MOSAIC_COLUMNS * 3;
+
+/// Black border between tiles, in pixels
+const GAP: u32 = 4;
+
+/// Images out of `budget` that are spent on mosaics; in mixed mode the rest go to full frames
+fn mosaic_budget(mode: FrameMode, budget: usize) -> usize {
+    match mode {
+        FrameMode::Full => 0,
+        FrameMode::Mosaic => budget,
+        FrameMode::Mixed => (budget / 2).max(1),
+    }
+}
+
+/// Frames that fit into the mosaics of `budget` images
+pub fn tile_budget(mode: FrameMode, budget: usize) -> usize {
+    mosaic_budget(mode, budget) * TILES_PER_MOSAIC
+}
+
+/// Sampling interval for a window whose full-frame interval is `interval`. Mosaics fit nine frames
+/// into one image, so the window is sampled that much more densely.
+pub fn sample_interval(mode: FrameMode, interval: u32, budget: usize) -> u32 {
+    let tiles = tile_budget(mode, budget);
+    if tiles == 0 {
+        return interval;
+    }
+
+    ((interval as usize * budget) / tiles).max(1) as u32
+}
+
+/// Tile `frames` in reading order into one grid image, each tile labelled with its offset
+/// and capture time
+pub fn build_mosaic(frames: &[Frame], recording_started_at: Option<DateTime<Local>>) -> Result<Frame, VerificationError> {
+    let first = frames
+        .first()
+        .ok_or_else(|| VerificationError::InvalidResponse("Cannot build a mosaic from no frames".to_string()))?;
+
+    let columns = frames.len().min(MOSAIC_COLUMNS) as u32;
+    let rows = frames.len().div_ceil(MOSAIC_COLUMNS) as u32;
+    let tile_width = (MAX_FRAME_DIMENSION - GAP * (MOSAIC_COLUMNS as u32 - 1)) / MOSAIC_COLUMNS as u32;
+    let tile_height = (tile_width * first.height / first.width.max(1)).max(1);
+
+    let mut canvas = RgbImage::new(
+        columns * tile_width + (columns - 1) * GAP,
+        rows * tile_height + (rows - 1) * GAP,
+    );
+
+    for (idx, frame) in frames.iter().enumerate() {
+        let mut tile = imageops::resize(&decode_rgb(frame)?, tile_width, tile_height, FilterType::Triangle);
+        draw_label(&mut tile, &frame_label(frame, recording_started_at));
+
+        let column = idx as u32 % columns;
+        let row = idx as u32 / columns;
+        imageops::replace(
+            &mut canvas,
+            &tile,
+            i64::from(column * (tile_width + GAP)),
+            i64::from(row * (tile_height + GAP)),
+        );
+    }
+
+    Ok(Frame {
+        offset_seconds: first.offset_seconds,
+        width: canvas.width(),
+        height: canvas.height(),
+        data: encode_jpeg(&canvas)?,
+        end_offset_seconds: None,
+        tiles: frames.iter().map(|f| f.offset_seconds).collect(),
+    })
+}
+
+/// Indices of up to `budget` frames that differ from the frame before them by more than
+/// `threshold` hash bits, biggest changes first
+fn changed_frames(frames: &[Frame], threshold: u32, budget: usize) -> Result<Vec<usize>, VerificationError> {
+    let hashes = frames.iter().map(dhash).collect::<Result<Vec<_>, _>>()?;
+
+    let mut changes: Vec<(usize, u32)> = hashes
+        .windows(2)
+        .enumerate()
+        .map(|(idx, pair)| (idx + 1, hamming_distance(pair[0], pair[1])))
+        .filter(|&(_, distance)| distance > threshold)
+        .collect();
+    changes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    Ok(changes.into_iter().take(budget).map(|(idx, _)| idx).collect())
+}
+
+/// Turn sampled frames into the images sent for `mode`, at most `budget` of them. In mixed mode
+/// the frames where the screen changed most are also sent full-size, right after their mosaic.
+pub fn compose(
+    frames: Vec<Frame>,
+    mode: FrameMode,
+    budget: usize,
+    change_threshold: u32,
+    recording_started_at: Option<DateTime<Local>>,
+) -> Result<Vec<Frame>, VerificationError> {
+    let mosaics = mosaic_budget(mode, budget);
+    if mosaics == 0 || frames.is_empty() {
+        return Ok(frames);
+    }
+
+    let frames = spread(frames, tile_budget(mode, budget));
+    let mosaic_count = frames.len().div_ceil(TILES_PER_MOSAIC);
+    let zoomed = match mode {
+        FrameMode::Mixed => changed_frames(&frames, change_threshold, budget.saturating_sub(mosaic_count))?,
+        _ => Vec::new(),
+    };
+
+    let mut images = Vec::with_capacity(mosaic_count + zoomed.len());
+    for (chunk_idx, chunk) in frames.chunks(TILES_PER_MOSAIC).enumerate() {
+        images.push(build_mosaic(chunk, recording_started_at)?);
+
+        let first = chunk_idx * TILES_PER_MOSAIC;
+        for (idx, frame) in chunk.iter().enumerate() {
+            if zoomed.contains(&(first + idx)) {
+                images.push(frame.clone());
+            }
+        }
+    }
+
+    Ok(images)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::edge_frame;
+
+    #[test]
+    fn test_mosaic_grid_layout() {
+        let frames: Vec<Frame> = (0..7).map(|i| edge_frame(i as f64 * 20.0, 80)).collect();
+        let mosaic = build_mosaic(&frames, None).unwrap();
+
+        // 3 columns of 520px tiles and 3 rows, the last one partly filled
+        assert_eq!((mosaic.width, mosaic.height), (3 * 520 + 2 * GAP, 3 * 292 + 2 * GAP));
+        assert!(mosaic.width <= MAX_FRAME_DIMENSION);
+        assert_eq!(mosaic.tiles, vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0, 120.0]);
+        assert_eq!(mosaic.last_offset_seconds(), 120.0);
+
+        let img = decode_rgb(&mosaic).unwrap();
+        assert_eq!((img.width(), img.height()), (mosaic.width, mosaic.height));
+        assert!(img.get_pixel(mosaic.width - 10, mosaic.height - 10)[0] < 60, "empty cell should stay black");
+    }
+
+    #[test]
+    fn test_sampling_is_denser_for_mosaics() {
+        assert_eq!(sample_interval(FrameMode::Full, 45, 20), 45);
+        assert_eq!(sample_interval(FrameMode::Mosaic, 45, 20), 5);
+        assert_eq!(sample_interval(FrameMode::Mixed, 45, 20), 10);
+        assert_eq!(sample_interval(FrameMode::Mosaic, 5, 20), 1);
+    }
+
+    #[test]
+    fn test_mixed_mode_adds_changed_frames_after_their_mosaic() {
+        let frames: Vec<Frame> = (0..12)
+            .map(|i| edge_frame(i as f64 * 10.0, if (4..10).contains(&i) { 150 } else { 10 }))
+            .collect();
+
+        let images = compose(frames.clone(), FrameMode::Mixed, 4, 6, None).unwrap();
+        let layout: Vec<(usize, f64)> = images.iter().map(|f| (f.tiles.len(), f.offset_seconds)).collect();
+        assert_eq!(layout, vec![(9, 0.0), (0, 40.0), (3, 90.0), (0, 100.0)]);
+
+        let mosaics = compose(frames.clone(), FrameMode::Mosaic, 4, 6, None).unwrap();
+        assert_eq!(mosaics.iter().map(|f| f.tiles.len()).collect::<Vec<_>>(), vec![9, 3]);
+
+        assert_eq!(compose(frames, FrameMode::Full, 4, 6, None).unwrap().len(), 12);
+    }
+}
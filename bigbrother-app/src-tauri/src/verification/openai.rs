@@ -0,0 +1,266 @@
+use super::catalog::ModelCache;
+use super::error::VerificationError;
+use super::http::{HttpClient, RetryPolicy};
+use super::injection::system_prompt;
+use super::prompt::{build_prompt, frame_annotation, ResponseFormat};
+use super::parser::{check_against_request, parse_repaired_response, parse_verification_response, repair_messages, with_original_raw};
+use super::provider::{endpoint, VerificationProvider, VerificationRequest, MAX_FRAMES_PER_REQUEST};
+use super::usage::{TokenUsage, UsageLog};
+use crate::database::models::{ModelInfo, VerificationResult};
+use async_trait::async_trait;
+use serde_json::json;
+
+/// Any server exposing an OpenAI-compatible `/chat/completions` endpoint with vision support,
+/// e.g. a local llama.cpp server or Ollama
+pub struct OpenAiCompatibleProvider {
+    pub base_url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    http: HttpClient,
+    model_cache: Option<ModelCache>,
+    usage: UsageLog,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(base_url: String, model: String, api_key: Option<String>, retry: RetryPolicy) -> Self {
+        Self {
+            base_url,
+            model,
+            api_key,
+            http: HttpClient::new(retry),
+            model_cache: None,
+            usage: UsageLog::default(),
+        }
+    }
+
+    pub fn with_model_cache(mut self, cache: Option<ModelCache>) -> Self {
+        self.model_cache = cache;
+        self
+    }
+
+    /// Query `/models`, which most OpenAI-compatible servers implement
+    async fn fetch_models(&self) -> Result<Vec<ModelInfo>, VerificationError> {
+        let url = endpoint(&self.base_url, "models");
+        let models_json = self
+            .http
+            .send_json(&format!("Model list at {}", self.base_url), |client| {
+                let builder = client.get(&url);
+                match &self.api_key {
+                    Some(api_key) => builder.bearer_auth(api_key),
+                    None => builder,
+                }
+            })
+            .await?;
+
+        Ok(parse_model_list(&models_json))
+    }
+
+    /// The system prompt, then the prompt followed by the frames as data URL images in a single user turn
+    pub fn build_messages(&self, request: &VerificationRequest) -> serde_json::Value {
+        let mut content_parts: Vec<serde_json::Value> = vec![json!({
+            "type": "text",
+            "text": build_prompt(request, ResponseFormat::Json)
+        })];
+
+        for frame in request.frames.iter().take(MAX_FRAMES_PER_REQUEST) {
+            content_parts.push(json!({
+                "type": "text",
+                "text": frame_annotation(frame, request.recording_started_at)
+            }));
+            content_parts.push(json!({
+                "type": "image_url",
+                "image_url": {
+                    "url": format!("data:image/jpeg;base64,{}", frame.data)
+                }
+            }));
+        }
+
+        json!([
+            system_message(request),
+            {"role": "user", "content": content_parts}
+        ])
+    }
+
+    pub fn build_request_body(&self, messages: &serde_json::Value) -> serde_json::Value {
+        json!({
+            "model": self.model,
+            "max_tokens": 2048,
+            "temperature": 0,
+            "messages": messages
+        })
+    }
+
+    /// Send a chat completion and return the assistant's text
+    async fn complete(&self, messages: &serde_json::Value) -> Result<String, VerificationError> {
+        let url = endpoint(&self.base_url, "chat/completions");
+        let body = self.build_request_body(messages);
+
+        let response_json = self
+            .http
+            .send_json(&format!("Model {} at {}", self.model, self.base_url), |client| {
+                let builder = client.post(&url).json(&body);
+                match &self.api_key {
+                    Some(api_key) => builder.bearer_auth(api_key),
+                    None => builder,
+                }
+            })
+            .await?;
+
+        self.usage.record(TokenUsage::from_openai(&self.model, &response_json));
+
+        extract_text(&response_json).map(str::to_string)
+    }
+}
+
+/// Model ids from a `/models` response, newest first when the server reports creation times
+pub fn parse_model_list(models_json: &serde_json::Value) -> Vec<ModelInfo> {
+    let mut models: Vec<(i64, ModelInfo)> = models_json["data"]
+        .as_array()
+        .map(|data| data.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|m| {
+            let created = m["created"].as_i64().unwrap_or(0);
+            Some((
+                created,
+                ModelInfo {
+                    id: m["id"].as_str()?.to_string(),
+                    display_name: None,
+                    created_at: chrono::DateTime::from_timestamp(created, 0)
+                        .filter(|_| created > 0)
+                        .map(|t| t.to_rfc3339()),
+                },
+            ))
+        })
+        .collect();
+
+    models.sort_by_key(|(created, _)| std::cmp::Reverse(*created));
+    models.into_iter().map(|(_, model)| model).collect()
+}
+
+/// Keeps the app's instructions apart from the untrusted frames
+fn system_message(request: &VerificationRequest) -> serde_json::Value {
+    json!({"role": "system", "content": system_prompt(request.canary.as_deref())})
+}
+
+/// Extract the assistant message from a chat completion response
+pub fn extract_text(response_json: &serde_json::Value) -> Result<&str, VerificationError> {
+    response_json["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| VerificationError::InvalidResponse("Failed to extract text from chat completion".to_string()))
+}
+
+#[async_trait]
+impl VerificationProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        "openai_compatible"
+    }
+
+    async fn verify(&self, request: &VerificationRequest) -> Result<VerificationResult, VerificationError> {
+        let text = self.complete(&self.build_messages(request)).await?;
+
+        match parse_verification_response(&text).and_then(|result| check_against_request(result, request, &text)) {
+            Err(VerificationError::ResponseParse { problems, raw }) => {
+                eprintln!("Response from {} failed to parse ({}), requesting repair", self.model, problems.join("; "));
+
+                let mut messages = repair_messages(&build_prompt(request, ResponseFormat::Json), &raw, &problems);
+                if let Some(messages) = messages.as_array_mut() {
+                    messages.insert(0, system_message(request));
+                }
+                let repaired = self.complete(&messages).await?;
+                parse_repaired_response(&repaired, &raw)
+                    .and_then(|result| check_against_request(result, request, &repaired).map_err(|e| with_original_raw(e, &raw)))
+            }
+            result => result,
+        }
+    }
+
+    async fn list_models(&self, refresh: bool) -> Result<Vec<ModelInfo>, VerificationError> {
+        if !refresh {
+            if let Some(models) = self.model_cache.as_ref().and_then(|c| c.load(&self.base_url)) {
+                return Ok(models);
+            }
+        }
+
+        let models = self.fetch_models().await?;
+        if let Some(cache) = &self.model_cache {
+            if let Err(e) = cache.store(&self.base_url, &models) {
+                eprintln!("Failed to cache model list: {}", e);
+            }
+        }
+
+        Ok(models)
+    }
+
+    fn model_used(&self) -> Option<String> {
+        Some(self.model.clone())
+    }
+
+    fn take_usage(&self) -> Vec<TokenUsage> {
+        self.usage.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::template::builtin_template;
+    use crate::verification::frames::Frame;
+
+    #[test]
+    fn test_request_body_uses_data_urls() {
+        let provider = OpenAiCompatibleProvider::new(
+            "http://localhost:8080/v1".to_string(),
+            "llava".to_string(),
+            None,
+            RetryPolicy::default(),
+        );
+        let request = VerificationRequest {
+            title: "Read chapter 3".to_string(),
+            description: Some("Biology textbook".to_string()),
+            required_duration_minutes: 20,
+            actual_duration_minutes: 25.0,
+            frame_interval_seconds: 10,
+            frames: vec![Frame { offset_seconds: 0.0, width: 8, height: 8, data: "abc".to_string(), end_offset_seconds: None, tiles: Vec::new() }],
+            window: None,
+            template: builtin_template(),
+            appeal: None,
+            recording_started_at: None,
+            activity: None,
+            canary: Some("BB-TEST".to_string()),
+            criteria: vec![],
+        };
+
+        let body = provider.build_request_body(&provider.build_messages(&request));
+        let content = body["messages"][1]["content"].as_array().unwrap();
+
+        assert_eq!(body["model"], "llava");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert!(body["messages"][0]["content"].as_str().unwrap().contains("BB-TEST"));
+        assert_eq!(content[1]["type"], "text");
+        assert_eq!(content[2]["type"], "image_url");
+        assert_eq!(content[2]["image_url"]["url"], "data:image/jpeg;base64,abc");
+    }
+
+    #[test]
+    fn test_model_list_newest_first() {
+        let response = json!({"object": "list", "data": [
+            {"id": "llava:7b", "created": 1700000000},
+            {"id": "qwen2-vl", "created": 1720000000},
+            {"id": "no-date"}
+        ]});
+
+        let models = parse_model_list(&response);
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["qwen2-vl", "llava:7b", "no-date"]);
+        assert!(models[2].created_at.is_none());
+        assert!(parse_model_list(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_extract_text() {
+        let response = json!({"choices": [{"message": {"role": "assistant", "content": "{\"verified\": true}"}}]});
+        assert_eq!(extract_text(&response).unwrap(), "{\"verified\": true}");
+        assert!(extract_text(&json!({"choices": []})).is_err());
+    }
+}
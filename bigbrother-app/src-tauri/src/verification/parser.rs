@@ -0,0 +1,540 @@
+use super::error::VerificationError;
+use super::provider::VerificationRequest;
+use crate::database::models::{ActivityCategory, CriterionScore, TimelineEntry, VerificationResult};
+use serde_json::Value;
+
+/// Parse a model reply into a `VerificationResult`, tolerating code fences and surrounding prose
+pub fn parse_verification_response(text: &str) -> Result<VerificationResult, VerificationError> {
+    let failure = |problems: Vec<String>| VerificationError::ResponseParse {
+        problems,
+        raw: text.to_string(),
+    };
+
+    let json = extract_json_object(text)
+        .ok_or_else(|| failure(vec!["no JSON object found in the response".to_string()]))?;
+
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| failure(vec![format!("invalid JSON: {}", e)]))?;
+
+    validate_verification(&value).map_err(failure)
+}
+
+/// Validate an already structured value, e.g. the input of a tool call
+pub fn parse_verification_value(value: &Value) -> Result<VerificationResult, VerificationError> {
+    validate_verification(value).map_err(|problems| VerificationError::ResponseParse {
+        problems,
+        raw: value.to_string(),
+    })
+}
+
+/// Seconds a timeline entry may stray past the frames before it counts as out of range
+const TIMELINE_RANGE_TOLERANCE_SECONDS: f64 = 1.0;
+
+/// Timeline entries that start before the first frame or end after the time the last frame covers
+pub fn timeline_range_problems(timeline: &[TimelineEntry], (start, end): (f64, f64)) -> Vec<String> {
+    timeline
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| {
+            entry.start_seconds < start - TIMELINE_RANGE_TOLERANCE_SECONDS
+                || entry.end_seconds > end + TIMELINE_RANGE_TOLERANCE_SECONDS
+        })
+        .map(|(idx, entry)| {
+            format!(
+                "timeline[{}] covers {:.0}-{:.0} seconds but the frames only cover {:.0}-{:.0} seconds",
+                idx, entry.start_seconds, entry.end_seconds, start, end
+            )
+        })
+        .collect()
+}
+
+/// Whether a score's criterion names the success criterion, ignoring case, spacing and punctuation
+pub fn same_criterion(scored: &str, criterion: &str) -> bool {
+    let words = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    words(scored) == words(criterion)
+}
+
+/// Problems with the criterion scores when the task has success criteria: every criterion must be
+/// scored once, in order, under its own text. Each score takes the first criterion of its text
+/// that no earlier score took, so criteria repeated with different case still pair up.
+pub fn criteria_problems(scores: &[CriterionScore], criteria: &[String]) -> Vec<String> {
+    if criteria.is_empty() {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    let mut taken = vec![false; criteria.len()];
+    for (idx, score) in scores.iter().enumerate() {
+        let matches = |position: usize| same_criterion(&score.criterion, &criteria[position]);
+        let free = if idx < criteria.len() && !taken[idx] && matches(idx) {
+            Some(idx)
+        } else {
+            (0..criteria.len()).find(|&position| !taken[position] && matches(position))
+        };
+
+        match free {
+            Some(position) => {
+                taken[position] = true;
+                if position != idx {
+                    problems.push(format!(
+                        "criteria[{}] scores success criterion {} but criterion {} belongs there; list them in order",
+                        idx,
+                        position + 1,
+                        idx + 1
+                    ));
+                }
+            }
+            None => match (0..criteria.len()).find(|&position| matches(position)) {
+                Some(position) => problems.push(format!(
+                    "criteria[{}] scores success criterion {} a second time",
+                    idx,
+                    position + 1
+                )),
+                None => problems.push(format!(
+                    "criteria[{}] scores \"{}\", which is not one of the success criteria; copy each criterion's text exactly",
+                    idx, score.criterion
+                )),
+            },
+        }
+    }
+
+    for (idx, criterion) in criteria.iter().enumerate() {
+        if !taken[idx] {
+            problems.push(format!("success criterion {} (\"{}\") has no score", idx + 1, criterion));
+        }
+    }
+
+    problems
+}
+
+/// Reject a result whose timeline falls outside the frames of the request or that skips,
+/// reorders or renames success criteria, so it goes through the same repair turn as any other
+/// invalid reply
+pub fn check_against_request(
+    result: VerificationResult,
+    request: &VerificationRequest,
+    raw: &str,
+) -> Result<VerificationResult, VerificationError> {
+    let mut problems = request
+        .frame_range()
+        .map(|range| timeline_range_problems(&result.timeline, range))
+        .unwrap_or_default();
+    problems.extend(criteria_problems(&result.criteria, &request.criteria));
+
+    if !problems.is_empty() {
+        return Err(VerificationError::ResponseParse { problems, raw: raw.to_string() });
+    }
+
+    Ok(result)
+}
+
+/// Follow-up message asking the model to fix a reply that failed to parse
+pub fn repair_prompt(problems: &[String]) -> String {
+    format!(
+        "Your previous reply could not be used because of these problems:\n- {}\n\n\
+        Reply again with ONLY a single JSON object and no other text, with exactly these fields:\n\
+        \"verified\" (boolean), \"confidence\" (integer 0-100), \"time_on_task_minutes\" (number >= 0), \
+        \"explanation\" (string), \"issues\" (array of strings) and \
+        \"timeline\" (array of {{\"start_seconds\": number, \"end_seconds\": number, \
+        \"category\": \"on_task\" | \"off_task\" | \"idle\" | \"break\", \"activity\": string, \"label\": string or null}}). \
+        If the task has success criteria, also include \"criteria\" (array with one {{\"criterion\": string, \
+        \"score\": integer 0-100, \"evidence_seconds\": array of numbers, \"note\": string}} per criterion, in order, \
+        with the criterion's text copied exactly).",
+        problems.join("\n- ")
+    )
+}
+
+/// Conversation for the repair turn. The frames aren't re-sent: the model only needs to
+/// reformat its own answer, and images are by far the most expensive part of a request.
+pub fn repair_messages(prompt: &str, raw: &str, problems: &[String]) -> Value {
+    serde_json::json!([
+        {"role": "user", "content": prompt},
+        {"role": "assistant", "content": raw},
+        {"role": "user", "content": repair_prompt(problems)}
+    ])
+}
+
+/// Parse the reply to a repair turn, keeping both replies in the error if it still fails
+pub fn parse_repaired_response(text: &str, original_raw: &str) -> Result<VerificationResult, VerificationError> {
+    parse_verification_response(text).map_err(|e| with_original_raw(e, original_raw))
+}
+
+/// Prepend the first reply to the raw text of a failed repair
+pub fn with_original_raw(error: VerificationError, original_raw: &str) -> VerificationError {
+    match error {
+        VerificationError::ResponseParse { problems, raw } => VerificationError::ResponseParse {
+            problems,
+            raw: format!("{}\n\n--- repair attempt ---\n{}", original_raw, raw),
+        },
+        other => other,
+    }
+}
+
+/// Find the verification JSON in a reply: a fenced block if present, otherwise the first
+/// balanced `{...}` in the text
+pub fn extract_json_object(text: &str) -> Option<&str> {
+    let candidate = fenced_block(text).unwrap_or(text);
+    balanced_object(candidate).or_else(|| balanced_object(text))
+}
+
+fn fenced_block(text: &str) -> Option<&str> {
+    let start = text.find("```")?;
+    let after_fence = &text[start + 3..];
+    // Skip the language tag, e.g. ```json
+    let body_start = after_fence.find('\n')? + 1;
+    let body = &after_fence[body_start..];
+    let end = body.find("```")?;
+    Some(&body[..end])
+}
+
+fn balanced_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + offset + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Check a parsed JSON value against the `VerificationResult` shape, collecting every problem
+fn validate_verification(value: &Value) -> Result<VerificationResult, Vec<String>> {
+    let mut problems = Vec::new();
+
+    if !value.is_object() {
+        return Err(vec!["response is not a JSON object".to_string()]);
+    }
+
+    let verified = value["verified"].as_bool();
+    if verified.is_none() {
+        problems.push("\"verified\" must be a boolean".to_string());
+    }
+
+    let confidence = match value["confidence"].as_f64() {
+        Some(c) if (0.0..=100.0).contains(&c) => Some(c.round() as i64),
+        Some(c) => {
+            problems.push(format!("\"confidence\" must be between 0 and 100, got {}", c));
+            None
+        }
+        None => {
+            problems.push("\"confidence\" must be a number".to_string());
+            None
+        }
+    };
+
+    let time_on_task_minutes = match value["time_on_task_minutes"].as_f64() {
+        Some(t) if t >= 0.0 => Some(t),
+        Some(t) => {
+            problems.push(format!("\"time_on_task_minutes\" must not be negative, got {}", t));
+            None
+        }
+        None => {
+            problems.push("\"time_on_task_minutes\" must be a number".to_string());
+            None
+        }
+    };
+
+    let explanation = value["explanation"].as_str();
+    if explanation.is_none() {
+        problems.push("\"explanation\" must be a string".to_string());
+    }
+
+    let issues: Option<Vec<String>> = value["issues"]
+        .as_array()
+        .and_then(|items| items.iter().map(|i| i.as_str().map(str::to_string)).collect());
+    if issues.is_none() {
+        problems.push("\"issues\" must be an array of strings".to_string());
+    }
+
+    let mut timeline = Vec::new();
+    match value["timeline"].as_array() {
+        Some(entries) => {
+            for (idx, entry) in entries.iter().enumerate() {
+                match validate_timeline_entry(entry) {
+                    Ok(entry) => timeline.push(entry),
+                    Err(problem) => problems.push(format!("timeline[{}] {}", idx, problem)),
+                }
+            }
+        }
+        None => problems.push("\"timeline\" must be an array".to_string()),
+    }
+
+    // Only tasks with success criteria are asked for scores
+    let mut criteria = Vec::new();
+    match &value["criteria"] {
+        Value::Null => {}
+        Value::Array(entries) => {
+            for (idx, entry) in entries.iter().enumerate() {
+                match validate_criterion(entry) {
+                    Ok(score) => criteria.push(score),
+                    Err(problem) => problems.push(format!("criteria[{}] {}", idx, problem)),
+                }
+            }
+        }
+        _ => problems.push("\"criteria\" must be an array".to_string()),
+    }
+
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    Ok(VerificationResult {
+        verified: verified.unwrap_or_default(),
+        confidence: confidence.unwrap_or_default(),
+        time_on_task_minutes: time_on_task_minutes.unwrap_or_default(),
+        explanation: explanation.unwrap_or_default().to_string(),
+        issues: issues.unwrap_or_default(),
+        timeline,
+        criteria,
+    })
+}
+
+/// One criterion score: a 0-100 score, the offsets of the frames that show it and a note
+fn validate_criterion(entry: &Value) -> Result<CriterionScore, String> {
+    let criterion = entry["criterion"]
+        .as_str()
+        .ok_or_else(|| "must have a string \"criterion\"".to_string())?;
+
+    let score = match entry["score"].as_f64() {
+        Some(score) if (0.0..=100.0).contains(&score) => score.round() as i64,
+        Some(score) => return Err(format!("\"score\" must be between 0 and 100, got {}", score)),
+        None => return Err("must have a numeric \"score\"".to_string()),
+    };
+
+    let evidence_seconds = match &entry["evidence_seconds"] {
+        Value::Null => Vec::new(),
+        Value::Array(offsets) => offsets
+            .iter()
+            .map(|offset| offset.as_f64().filter(|seconds| *seconds >= 0.0))
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(|| "\"evidence_seconds\" must hold offsets in seconds".to_string())?,
+        _ => return Err("\"evidence_seconds\" must be an array".to_string()),
+    };
+
+    Ok(CriterionScore {
+        criterion: criterion.to_string(),
+        score,
+        evidence_seconds,
+        note: entry["note"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// One timeline entry: offsets in seconds with end after start, a known category and an
+/// optional app or site label
+fn validate_timeline_entry(entry: &Value) -> Result<TimelineEntry, String> {
+    let offset = |field: &str| match entry[field].as_f64() {
+        Some(seconds) if seconds >= 0.0 => Ok(seconds),
+        Some(seconds) => Err(format!("\"{}\" must not be negative, got {}", field, seconds)),
+        None => Err(format!("must have a numeric \"{}\"", field)),
+    };
+    let start_seconds = offset("start_seconds")?;
+    let end_seconds = offset("end_seconds")?;
+    if end_seconds < start_seconds {
+        return Err(format!("ends ({}) before it starts ({})", end_seconds, start_seconds));
+    }
+
+    let category = entry["category"].as_str().unwrap_or_default();
+    let category = ActivityCategory::parse(category)
+        .ok_or_else(|| format!("\"category\" must be on_task, off_task, idle or break, got \"{}\"", category))?;
+
+    let activity = entry["activity"]
+        .as_str()
+        .ok_or_else(|| "must have a string \"activity\"".to_string())?;
+
+    let label = match &entry["label"] {
+        Value::Null => None,
+        Value::String(label) if label.trim().is_empty() => None,
+        Value::String(label) => Some(label.trim().to_string()),
+        _ => return Err("\"label\" must be a string or null".to_string()),
+    };
+
+    Ok(TimelineEntry {
+        start_seconds,
+        end_seconds,
+        category,
+        activity: activity.to_string(),
+        label,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{
+        "verified": true,
+        "confidence": 87,
+        "time_on_task_minutes": 31.5,
+        "explanation": "User worked through calculus problems {with braces}",
+        "issues": [],
+        "timeline": [
+            {"start_seconds": 0, "end_seconds": 120, "category": "on_task", "activity": "Opened textbook", "label": null},
+            {"start_seconds": 120, "end_seconds": 300, "category": "off_task", "activity": "Watching videos", "label": "youtube.com"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parses_plain_json() {
+        let result = parse_verification_response(VALID).unwrap();
+        assert!(result.verified);
+        assert_eq!(result.confidence, 87);
+        assert_eq!(result.timeline.len(), 2);
+        assert_eq!(result.timeline[1].category, ActivityCategory::OffTask);
+        assert_eq!(result.timeline[1].label.as_deref(), Some("youtube.com"));
+    }
+
+    #[test]
+    fn test_parses_fenced_json() {
+        let text = format!("Here is my analysis:\n```json\n{}\n```\nLet me know!", VALID);
+        assert_eq!(parse_verification_response(&text).unwrap().confidence, 87);
+    }
+
+    #[test]
+    fn test_parses_prose_wrapped_json() {
+        let text = format!("Based on the frames, {} Overall the user did well.", VALID);
+        let result = parse_verification_response(&text).unwrap();
+        assert_eq!(result.explanation, "User worked through calculus problems {with braces}");
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_confidence_and_bad_timeline() {
+        let text = r#"{"verified": true, "confidence": 140, "time_on_task_minutes": 30,
+            "explanation": "ok", "issues": [], "timeline": [{"start_seconds": 60, "end_seconds": 30, "category": "on_task", "activity": "x"}]}"#;
+
+        match parse_verification_response(text) {
+            Err(VerificationError::ResponseParse { problems, raw }) => {
+                assert_eq!(problems.len(), 2);
+                assert!(problems[0].contains("confidence"));
+                assert!(problems[1].contains("timeline[0] ends"));
+                assert_eq!(raw, text);
+            }
+            other => panic!("expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reports_missing_json() {
+        let result = parse_verification_response("I cannot help with that.");
+        assert!(matches!(result, Err(VerificationError::ResponseParse { .. })));
+    }
+
+    #[test]
+    fn test_failed_repair_keeps_both_replies() {
+        match parse_repaired_response("still not json", "first reply") {
+            Err(VerificationError::ResponseParse { raw, .. }) => {
+                assert!(raw.starts_with("first reply"));
+                assert!(raw.ends_with("still not json"));
+            }
+            other => panic!("expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_timeline_entry_validation() {
+        let entry = |value: Value| validate_timeline_entry(&value);
+
+        let idle = entry(serde_json::json!({"start_seconds": 10, "end_seconds": 10.5, "category": "idle", "activity": "Away", "label": "  "})).unwrap();
+        assert_eq!(idle.category, ActivityCategory::Idle);
+        assert!(idle.label.is_none());
+
+        assert!(entry(serde_json::json!({"start_seconds": 0, "end_seconds": 5, "category": "napping", "activity": "x"})).is_err());
+        assert!(entry(serde_json::json!({"start_seconds": -1, "end_seconds": 5, "category": "break", "activity": "x"})).is_err());
+        assert!(entry(serde_json::json!({"start_seconds": 0, "end_seconds": 5, "category": "break", "activity": "x", "label": 3})).is_err());
+        assert!(entry(serde_json::json!({"timestamp": "00:00", "activity": "x"})).is_err());
+    }
+
+    #[test]
+    fn test_timeline_must_stay_within_frames() {
+        let result = parse_verification_response(VALID).unwrap();
+
+        assert!(timeline_range_problems(&result.timeline, (0.0, 300.0)).is_empty());
+        assert!(timeline_range_problems(&result.timeline, (0.5, 300.5)).is_empty());
+
+        let problems = timeline_range_problems(&result.timeline, (60.0, 240.0));
+        assert_eq!(problems.len(), 2);
+        assert!(problems[1].starts_with("timeline[1] covers 120-300 seconds"));
+    }
+
+    #[test]
+    fn test_criteria_scores() {
+        let with_scores = VALID.replacen(
+            "\"issues\": [],",
+            r#""issues": [], "criteria": [{"criterion": "5 problems", "score": 80.4, "evidence_seconds": [30, 95.5], "note": "Worksheet"}],"#,
+            1,
+        );
+        let result = parse_verification_response(&with_scores).unwrap();
+        assert_eq!(result.criteria[0].score, 80);
+        assert_eq!(result.criteria[0].evidence_seconds, vec![30.0, 95.5]);
+        assert!(parse_verification_response(VALID).unwrap().criteria.is_empty());
+
+        let bad = VALID.replacen("\"issues\": [],", r#""issues": [], "criteria": [{"criterion": "x", "score": 150}],"#, 1);
+        match parse_verification_response(&bad) {
+            Err(VerificationError::ResponseParse { problems, .. }) => assert!(problems[0].starts_with("criteria[0]")),
+            other => panic!("expected parse error, got {:?}", other),
+        }
+
+        let criteria = vec!["Solved at least 5 practice problems".to_string(), "Checked the answers".to_string()];
+        let score = |criterion: &str| CriterionScore { criterion: criterion.to_string(), ..CriterionScore::default() };
+        assert!(criteria_problems(&result.criteria, &[]).is_empty());
+        assert!(criteria_problems(&[score("solved at least 5 practice problems."), score("Checked the answers")], &criteria).is_empty());
+
+        assert_eq!(
+            criteria_problems(&[score("Solved at least 5 practice problems")], &criteria),
+            vec!["success criterion 2 (\"Checked the answers\") has no score"]
+        );
+        assert_eq!(
+            criteria_problems(&[score("Checked the answers"), score("Solved at least 5 practice problems")], &criteria),
+            vec![
+                "criteria[0] scores success criterion 2 but criterion 1 belongs there; list them in order",
+                "criteria[1] scores success criterion 1 but criterion 2 belongs there; list them in order",
+            ]
+        );
+        assert_eq!(
+            criteria_problems(&result.criteria, &criteria[..1]),
+            vec![
+                "criteria[0] scores \"5 problems\", which is not one of the success criteria; copy each criterion's text exactly",
+                "success criterion 1 (\"Solved at least 5 practice problems\") has no score",
+            ]
+        );
+        assert_eq!(
+            criteria_problems(&[score("Checked the answers"), score("Checked the answers")], &criteria[1..]),
+            vec!["criteria[1] scores success criterion 1 a second time"]
+        );
+
+        // Criteria that only differ in case each take one score
+        let repeated = vec!["Run 5k".to_string(), "run 5k.".to_string()];
+        assert!(criteria_problems(&[score("Run 5k"), score("run 5k.")], &repeated).is_empty());
+        assert_eq!(
+            criteria_problems(&[score("Run 5k")], &repeated),
+            vec!["success criterion 2 (\"run 5k.\") has no score"]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::database::models::{PolicyDecision, PolicyInputs, Verdict, VerificationPolicy, VerificationResult};
+
+/// Results of the checks run on the recording itself, which the model's report can't override
+#[derive(Debug, Clone, Default)]
+pub struct LocalFindings {
+    pub review_flags: Vec<String>, // keep a pass from being final
+    pub vetoes: Vec<String>,       // fail the verdict outright
+}
+
+/// Decide the verdict from the model's report instead of trusting its `verified` flag alone.
+///
+/// Local vetoes fail the verdict whatever the report says. Otherwise a report below the confidence
+/// threshold needs review, and it fails if the model failed it, too little time was on task or it
+/// lists too many issues. When the task has success criteria, their scores take the place of the
+/// model's verdict: every criterion must reach the minimum score. A pass with local review flags
+/// needs review instead.
+pub fn evaluate(
+    policy: &VerificationPolicy,
+    result: &VerificationResult,
+    required_minutes: f64,
+    local: &LocalFindings,
+) -> PolicyDecision {
+    let review_flags = &local.review_flags;
+    let inputs = PolicyInputs {
+        model_verified: result.verified,
+        confidence: result.confidence,
+        time_on_task_minutes: result.time_on_task_minutes,
+        required_minutes,
+        issue_count: result.issues.len(),
+        review_flags: review_flags.clone(),
+        vetoes: local.vetoes.clone(),
+        criterion_scores: result.criteria.iter().map(|c| c.score).collect(),
+        policy: policy.clone(),
+    };
+
+    if !local.vetoes.is_empty() {
+        return PolicyDecision {
+            verdict: Verdict::Fail,
+            reasons: local.vetoes.clone(),
+            inputs,
+        };
+    }
+
+    if result.confidence < policy.min_confidence {
+        return PolicyDecision {
+            verdict: Verdict::NeedsReview,
+            reasons: vec![format!(
+                "Confidence {}% is below the {}% threshold",
+                result.confidence, policy.min_confidence
+            )],
+            inputs,
+        };
+    }
+
+    let mut reasons = Vec::new();
+
+    if result.criteria.is_empty() && !result.verified {
+        reasons.push("The model did not find the task completed".to_string());
+    }
+
+    for criterion in result.criteria.iter().filter(|c| c.score < policy.min_criterion_score) {
+        reasons.push(format!(
+            "Criterion \"{}\" scored {}, {} required",
+            criterion.criterion, criterion.score, policy.min_criterion_score
+        ));
+    }
+
+    let needed_minutes = required_minutes * policy.min_time_on_task_ratio;
+    if result.time_on_task_minutes < needed_minutes {
+        reasons.push(format!(
+            "{:.1} minutes on task, {:.1} required",
+            result.time_on_task_minutes, needed_minutes
+        ));
+    }
+
+    if let Some(max_issues) = policy.max_issues {
+        if result.issues.len() > max_issues {
+            reasons.push(format!("{} issues reported, at most {} allowed", result.issues.len(), max_issues));
+        }
+    }
+
+    if reasons.is_empty() && !review_flags.is_empty() {
+        PolicyDecision {
+            verdict: Verdict::NeedsReview,
+            reasons: review_flags.clone(),
+            inputs,
+        }
+    } else if reasons.is_empty() {
+        PolicyDecision {
+            verdict: Verdict::Pass,
+            reasons: vec![format!(
+                "{:.1} minutes on task at {}% confidence",
+                result.time_on_task_minutes, result.confidence
+            )],
+            inputs,
+        }
+    } else {
+        PolicyDecision { verdict: Verdict::Fail, reasons, inputs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CriterionScore;
+    use crate::verification::test_support::test_result;
+
+    fn result(verified: bool, confidence: i64, minutes: f64, issues: usize) -> VerificationResult {
+        VerificationResult {
+            time_on_task_minutes: minutes,
+            issues: (0..issues).map(|i| format!("issue {}", i)).collect(),
+            ..test_result(verified, confidence)
+        }
+    }
+
+    #[test]
+    fn test_low_confidence_needs_review_either_way() {
+        let policy = VerificationPolicy::default();
+
+        for verified in [true, false] {
+            let decision = evaluate(&policy, &result(verified, 40, 30.0, 0), 30.0, &LocalFindings::default());
+            assert_eq!(decision.verdict, Verdict::NeedsReview);
+        }
+    }
+
+    #[test]
+    fn test_time_on_task_overrides_model_pass() {
+        let policy = VerificationPolicy::default();
+
+        assert_eq!(evaluate(&policy, &result(true, 90, 30.0, 0), 30.0, &LocalFindings::default()).verdict, Verdict::Pass);
+
+        let decision = evaluate(&policy, &result(true, 90, 20.0, 0), 30.0, &LocalFindings::default());
+        assert_eq!(decision.verdict, Verdict::Fail);
+        assert_eq!(decision.reasons, vec!["20.0 minutes on task, 30.0 required"]);
+        assert_eq!(decision.inputs.required_minutes, 30.0);
+
+        let lenient = VerificationPolicy { min_time_on_task_ratio: 0.5, ..policy };
+        assert_eq!(evaluate(&lenient, &result(true, 90, 20.0, 0), 30.0, &LocalFindings::default()).verdict, Verdict::Pass);
+    }
+
+    #[test]
+    fn test_model_fail_and_issue_limit() {
+        let policy = VerificationPolicy { max_issues: Some(1), ..VerificationPolicy::default() };
+
+        let decision = evaluate(&policy, &result(false, 90, 10.0, 3), 30.0, &LocalFindings::default());
+        assert_eq!(decision.verdict, Verdict::Fail);
+        assert_eq!(decision.reasons.len(), 3);
+        assert_eq!(evaluate(&policy, &result(true, 90, 45.0, 1), 30.0, &LocalFindings::default()).verdict, Verdict::Pass);
+    }
+
+    #[test]
+    fn test_review_flags_hold_back_a_pass_only() {
+        let policy = VerificationPolicy::default();
+        let flags = LocalFindings { review_flags: vec!["Mostly idle".to_string()], ..LocalFindings::default() };
+
+        let decision = evaluate(&policy, &result(true, 90, 30.0, 1), 30.0, &flags);
+        assert_eq!(decision.verdict, Verdict::NeedsReview);
+        assert_eq!(decision.reasons, flags.review_flags);
+        assert_eq!(decision.inputs.review_flags, flags.review_flags);
+
+        assert_eq!(evaluate(&policy, &result(false, 90, 30.0, 1), 30.0, &flags).verdict, Verdict::Fail);
+    }
+
+    #[test]
+    fn test_vetoes_fail_any_report() {
+        let policy = VerificationPolicy::default();
+        let local = LocalFindings { vetoes: vec!["Repeated footage".to_string()], ..LocalFindings::default() };
+
+        for confidence in [95, 20] {
+            let decision = evaluate(&policy, &result(true, confidence, 30.0, 0), 30.0, &local);
+            assert_eq!(decision.verdict, Verdict::Fail);
+            assert_eq!(decision.reasons, local.vetoes);
+            assert_eq!(decision.inputs.vetoes, local.vetoes);
+        }
+    }
+
+    #[test]
+    fn test_criteria_scores_decide_the_verdict() {
+        let policy = VerificationPolicy::default();
+        let score = |criterion: &str, score: i64| CriterionScore { criterion: criterion.to_string(), score, ..CriterionScore::default() };
+
+        // The model's own flag is ignored once criteria are scored
+        let met = VerificationResult { criteria: vec![score("5 problems", 90), score("Tests passing", 70)], ..result(false, 90, 30.0, 0) };
+        let decision = evaluate(&policy, &met, 30.0, &LocalFindings::default());
+        assert_eq!(decision.verdict, Verdict::Pass);
+        assert_eq!(decision.inputs.criterion_scores, vec![90, 70]);
+
+        let unmet = VerificationResult { criteria: vec![score("5 problems", 90), score("Tests passing", 40)], ..result(true, 90, 30.0, 0) };
+        let decision = evaluate(&policy, &unmet, 30.0, &LocalFindings::default());
+        assert_eq!(decision.verdict, Verdict::Fail);
+        assert_eq!(decision.reasons, vec!["Criterion \"Tests passing\" scored 40, 70 required"]);
+    }
+}
@@ -0,0 +1,166 @@
+use super::chunking::plan_windows;
+use super::frames::MAX_FRAME_DIMENSION;
+use super::provider::MAX_FRAMES_PER_REQUEST;
+use crate::database::models::CostEstimate;
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPrice {
+    pub fn cost_usd(&self, input_tokens: i64, output_tokens: i64) -> f64 {
+        (input_tokens as f64 * self.input_per_mtok + output_tokens as f64 * self.output_per_mtok) / 1_000_000.0
+    }
+}
+
+/// Prices matched by substring of the model id, most specific first
+const PRICE_TABLE: &[(&str, ModelPrice)] = &[
+    ("opus-4-5", ModelPrice { input_per_mtok: 5.0, output_per_mtok: 25.0 }),
+    ("opus", ModelPrice { input_per_mtok: 15.0, output_per_mtok: 75.0 }),
+    ("sonnet", ModelPrice { input_per_mtok: 3.0, output_per_mtok: 15.0 }),
+    ("haiku-4-5", ModelPrice { input_per_mtok: 1.0, output_per_mtok: 5.0 }),
+    ("3-5-haiku", ModelPrice { input_per_mtok: 0.8, output_per_mtok: 4.0 }),
+    ("haiku", ModelPrice { input_per_mtok: 0.25, output_per_mtok: 1.25 }),
+    ("gpt-4o-mini", ModelPrice { input_per_mtok: 0.15, output_per_mtok: 0.6 }),
+    ("gpt-4o", ModelPrice { input_per_mtok: 2.5, output_per_mtok: 10.0 }),
+];
+
+/// Used for Claude models missing from the table, so new releases are never counted as free
+const UNKNOWN_CLAUDE_PRICE: ModelPrice = ModelPrice { input_per_mtok: 3.0, output_per_mtok: 15.0 };
+
+/// Model used for estimates when none is given
+pub const DEFAULT_ESTIMATE_MODEL: &str = "claude-3-5-sonnet-20241022";
+
+/// Text prompt plus the report tool schema, per request
+pub const PROMPT_TOKENS: i64 = 1_000;
+
+/// Typical size of a report_verification tool call
+pub const EXPECTED_OUTPUT_TOKENS: i64 = 700;
+
+/// Size of the combined recording when the caller doesn't know better
+pub const DEFAULT_VIDEO_SIZE: (u32, u32) = (1920, 1080);
+
+/// Anthropic downscales images over ~1.15 megapixels, which caps an image at about this many tokens
+const MAX_IMAGE_TOKENS: i64 = 1_600;
+
+/// Price for a model id. Models we have no price for (e.g. local ones) cost nothing.
+pub fn model_price(model: &str) -> ModelPrice {
+    let model = model.to_lowercase();
+
+    PRICE_TABLE
+        .iter()
+        .find(|(pattern, _)| model.contains(pattern))
+        .map(|(_, price)| *price)
+        .unwrap_or(if model.contains("claude") {
+            UNKNOWN_CLAUDE_PRICE
+        } else {
+            ModelPrice { input_per_mtok: 0.0, output_per_mtok: 0.0 }
+        })
+}
+
+/// Input tokens for one image, using Anthropic's `width * height / 750` rule
+pub fn image_tokens(width: u32, height: u32) -> i64 {
+    let tokens = (width as f64 * height as f64 / 750.0).ceil() as i64;
+    tokens.min(MAX_IMAGE_TOKENS)
+}
+
+/// Size of a frame extracted from a `width`x`height` video, after `FrameExtractor` resizing
+pub fn frame_dimensions(width: u32, height: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= MAX_FRAME_DIMENSION {
+        return (width, height);
+    }
+
+    let scale = MAX_FRAME_DIMENSION as f64 / longest as f64;
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Input tokens for one request carrying frames of the given sizes
+pub fn request_input_tokens<I: IntoIterator<Item = (u32, u32)>>(frame_sizes: I) -> i64 {
+    PROMPT_TOKENS
+        + frame_sizes
+            .into_iter()
+            .map(|(width, height)| image_tokens(width, height))
+            .sum::<i64>()
+}
+
+/// Projected cost of verifying a recording, following the same windowing and frame sampling
+/// as the real run. Deduplication can only lower the frame count, so this is an upper bound.
+pub fn estimate_cost(
+    duration_seconds: f64,
+    window_seconds: u32,
+    min_interval_seconds: u32,
+    video_size: (u32, u32),
+    model: &str,
+) -> CostEstimate {
+    let (width, height) = frame_dimensions(video_size.0, video_size.1);
+    let mut frames = 0i64;
+    let mut input_tokens = 0i64;
+    let mut requests = 0i64;
+
+    for window in plan_windows(duration_seconds, window_seconds) {
+        let interval = window.frame_interval_seconds(min_interval_seconds) as f64;
+        let count = ((window.duration_seconds() / interval).ceil() as usize).clamp(1, MAX_FRAMES_PER_REQUEST);
+
+        frames += count as i64;
+        input_tokens += request_input_tokens(std::iter::repeat_n((width, height), count));
+        requests += 1;
+    }
+
+    let output_tokens = requests * EXPECTED_OUTPUT_TOKENS;
+
+    CostEstimate {
+        estimated_tokens: input_tokens + output_tokens,
+        estimated_cost_usd: model_price(model).cost_usd(input_tokens, output_tokens),
+        model: model.to_string(),
+        frames,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_price_lookup() {
+        assert_eq!(model_price("claude-3-5-sonnet-20241022").input_per_mtok, 3.0);
+        assert_eq!(model_price("claude-3-opus-20240229").output_per_mtok, 75.0);
+        assert_eq!(model_price("claude-opus-4-5-20251101").input_per_mtok, 5.0);
+        assert_eq!(model_price("claude-3-5-haiku-20241022").input_per_mtok, 0.8);
+        assert_eq!(model_price("claude-future-1"), UNKNOWN_CLAUDE_PRICE);
+        assert_eq!(model_price("llava").cost_usd(1_000_000, 1_000_000), 0.0);
+    }
+
+    #[test]
+    fn test_image_tokens_from_dimensions() {
+        assert_eq!(frame_dimensions(1920, 1080), (1568, 882));
+        assert_eq!(frame_dimensions(640, 480), (640, 480));
+        assert_eq!(image_tokens(640, 480), 410);
+        assert_eq!(image_tokens(1568, 882), MAX_IMAGE_TOKENS);
+        assert_eq!(request_input_tokens([(640, 480), (640, 480)]), PROMPT_TOKENS + 820);
+    }
+
+    #[test]
+    fn test_estimate_follows_windows() {
+        let short = estimate_cost(95.0, 15 * 60, 10, (640, 480), DEFAULT_ESTIMATE_MODEL);
+        assert_eq!(short.frames, 10);
+        assert_eq!(short.estimated_tokens, PROMPT_TOKENS + 10 * 410 + EXPECTED_OUTPUT_TOKENS);
+
+        let long = estimate_cost(3.0 * 3600.0, 15 * 60, 10, DEFAULT_VIDEO_SIZE, DEFAULT_ESTIMATE_MODEL);
+        assert_eq!(long.frames, 12 * MAX_FRAMES_PER_REQUEST as i64);
+        assert!(long.estimated_cost_usd > short.estimated_cost_usd);
+        assert_eq!(estimate_cost(95.0, 15 * 60, 10, (640, 480), "llava").estimated_cost_usd, 0.0);
+    }
+
+    #[test]
+    fn test_cost() {
+        let price = model_price("claude-3-5-sonnet-20241022");
+        assert!((price.cost_usd(10_000, 1_000) - 0.045).abs() < 1e-9);
+    }
+}
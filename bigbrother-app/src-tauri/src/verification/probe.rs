@@ -0,0 +1,90 @@
+use super::error::VerificationError;
+use std::path::Path;
+use std::process::Command;
+
+/// Read the container duration of a video with ffprobe
+pub fn probe_duration_seconds(video_path: &str) -> Result<f64, VerificationError> {
+    if !Path::new(video_path).is_file() {
+        return Err(VerificationError::VideoNotFound(video_path.to_string()));
+    }
+
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "error",
+            "-show_entries", "format=duration",
+            "-of", "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(video_path)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => VerificationError::FfmpegNotFound,
+            _ => VerificationError::Io(e),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(VerificationError::CorruptVideo(format!("{}: {}", video_path, stderr)));
+    }
+
+    parse_ffprobe_duration(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        VerificationError::CorruptVideo(format!("{} (could not determine duration)", video_path))
+    })
+}
+
+/// Recordings may be cut a few seconds short by FFmpeg start-up latency
+const DURATION_GRACE_SECONDS: f64 = 5.0;
+
+/// Reject recordings that can't possibly satisfy the task, without calling any model
+pub fn check_minimum_duration(actual_seconds: f64, required_seconds: i64) -> Result<(), VerificationError> {
+    if actual_seconds + DURATION_GRACE_SECONDS < required_seconds as f64 {
+        return Err(VerificationError::RecordingTooShort {
+            actual_seconds,
+            required_seconds,
+        });
+    }
+
+    Ok(())
+}
+
+/// ffprobe prints the duration in seconds, or "N/A" when the container doesn't know it
+fn parse_ffprobe_duration(stdout: &str) -> Option<f64> {
+    stdout
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::test_clip;
+
+    #[test]
+    fn test_parse_ffprobe_duration() {
+        assert_eq!(parse_ffprobe_duration("1834.560000\n"), Some(1834.56));
+        assert_eq!(parse_ffprobe_duration("N/A\n"), None);
+        assert_eq!(parse_ffprobe_duration(""), None);
+    }
+
+    #[test]
+    fn test_check_minimum_duration() {
+        assert!(check_minimum_duration(1800.0, 1800).is_ok());
+        assert!(check_minimum_duration(1797.5, 1800).is_ok());
+        assert!(matches!(
+            check_minimum_duration(1200.0, 1800),
+            Err(VerificationError::RecordingTooShort { required_seconds: 1800, .. })
+        ));
+    }
+
+    #[test]
+    fn test_probe_lavfi_clip() {
+        let Some(clip) = test_clip(6, "160x120") else { return };
+
+        let duration = probe_duration_seconds(&clip.to_string_lossy());
+        let _ = std::fs::remove_file(&clip);
+
+        let duration = duration.unwrap();
+        assert!((duration - 6.0).abs() < 0.5, "got {}", duration);
+    }
+}
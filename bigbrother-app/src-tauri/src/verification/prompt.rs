@@ -0,0 +1,271 @@
+use super::chunking::{format_hms, TimeWindow};
+use crate::database::models::ActivityProfile;
+use chrono::{DateTime, Local};
+use super::frames::Frame;
+use super::provider::{Appeal, VerificationRequest};
+use super::template::render;
+use super::tool::REPORT_TOOL_NAME;
+
+/// Idle stretches listed in the prompt; long idle recordings only need a few examples
+const MAX_IDLE_SPANS_LISTED: usize = 10;
+
+/// How the model is asked to return its verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json, // JSON object in the reply text
+    Tool, // forced call to the report_verification tool
+}
+
+/// Build the text instructions sent alongside the frames: the request's template, then the
+/// segment note and answer format, which don't vary between templates
+pub fn build_prompt(request: &VerificationRequest, format: ResponseFormat) -> String {
+    let mut prompt = render(&request.template, request);
+
+    if let Some(window) = &request.window {
+        prompt.push_str(&format!(
+            "\n\nThese frames cover segment {} of {} ({}) of a longer recording. \
+            Judge only this segment: set \"verified\" to true if the user was on task for most of it, \
+            report time_on_task_minutes for this segment only (at most {:.1} minutes), \
+            and give timeline offsets in seconds from the start of the full recording.",
+            window.index + 1,
+            window.count,
+            window.label(),
+            window.duration_seconds() / 60.0
+        ));
+    }
+
+    if let Some(appeal) = &request.appeal {
+        prompt.push_str(&appeal_context(appeal));
+    }
+
+    if let Some(activity) = &request.activity {
+        prompt.push_str(&activity_context(activity, request.window.as_ref()));
+    }
+
+    prompt.push_str(&criteria_context(&request.criteria, request.window.is_some()));
+
+    match format {
+        ResponseFormat::Json => prompt.push_str(
+            "\n\nProvide your response in JSON format:\n\
+            {\n\
+              \"verified\": true/false,\n\
+              \"confidence\": 0-100,\n\
+              \"time_on_task_minutes\": number,\n\
+              \"explanation\": \"detailed explanation\",\n\
+              \"issues\": [\"issue 1\", \"issue 2\"],\n\
+              \"timeline\": [\n\
+                {\"start_seconds\": 0, \"end_seconds\": 120, \"category\": \"on_task|off_task|idle|break\", \
+            \"activity\": \"description\", \"label\": \"app or website, or null\"}\n\
+              ]\n\
+            }",
+        ),
+        ResponseFormat::Tool => prompt.push_str(&format!(
+            "\n\nReport your findings by calling the {} tool.",
+            REPORT_TOOL_NAME
+        )),
+    }
+
+    prompt.push_str(
+        "\n\nCover the recording with consecutive timeline entries, starting a new entry wherever the \
+        activity changes. Offsets are seconds from the start of the recording; each frame is preceded by \
+        a note giving its offset and capture time.",
+    );
+
+    if let Some((start, end)) = request.frame_range() {
+        prompt.push_str(&format!(
+            " The frames cover offsets {:.0} to {:.0} seconds; keep every timeline entry within that range.",
+            start, end
+        ));
+    }
+
+    prompt
+}
+
+/// The earlier verdict and the user's appeal. The appeal is a claim to check against the frames,
+/// not evidence in itself.
+fn appeal_context(appeal: &Appeal) -> String {
+    let previous = &appeal.previous;
+    let issues = if previous.issues.is_empty() {
+        "none".to_string()
+    } else {
+        previous.issues.join("; ")
+    };
+
+    format!(
+        "\n\nThis recording was verified before and the user has appealed the verdict.\n\
+        Previous verdict: {} (confidence {}%, {:.1} minutes on task)\n\
+        Previous explanation: {}\n\
+        Previous issues: {}\n\
+        User's appeal: \"{}\"\n\
+        Re-examine the frames with the appeal in mind. Treat the appeal as the user's claim, not as evidence: \
+        change the verdict only where the frames support it, and say in the explanation how the appeal was weighed.",
+        if previous.verified { "verified" } else { "not verified" },
+        previous.confidence,
+        previous.time_on_task_minutes,
+        previous.explanation,
+        issues,
+        appeal.justification.trim()
+    )
+}
+
+/// Idle stretches found by comparing frames locally, limited to the segment being judged. They
+/// are hints for the timeline, not a verdict.
+fn activity_context(activity: &ActivityProfile, window: Option<&TimeWindow>) -> String {
+    let spans: Vec<String> = activity
+        .idle_spans
+        .iter()
+        .filter(|span| window.is_none_or(|w| span.end_seconds > w.start_seconds && span.start_seconds < w.end_seconds))
+        .take(MAX_IDLE_SPANS_LISTED)
+        .map(|span| format!("{}-{}", format_hms(span.start_seconds), format_hms(span.end_seconds)))
+        .collect();
+    if spans.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        "\n\nA local comparison of frames found no {} activity for {:.1} of {:.1} minutes ({:.0}%). \
+        Idle stretches: {}. Mark these as idle in the timeline unless the frames show the user was working \
+        away from the screen.",
+        if activity.webcam_checked { "screen or webcam" } else { "screen" },
+        activity.idle_seconds / 60.0,
+        (activity.idle_seconds + activity.active_seconds) / 60.0,
+        activity.idle_ratio() * 100.0,
+        spans.join(", ")
+    )
+}
+
+/// The task's success criteria, which the verdict is computed from, each scored on its own
+fn criteria_context(criteria: &[String], segment: bool) -> String {
+    if criteria.is_empty() {
+        return String::new();
+    }
+
+    let listed: Vec<String> = criteria
+        .iter()
+        .enumerate()
+        .map(|(idx, criterion)| format!("{}. {}", idx + 1, criterion))
+        .collect();
+
+    // Segment scores are added up, so a criterion met bit by bit across segments still passes
+    let scale = if segment {
+        " by how much of it this segment shows done, from 0 (none of it) to 100 (all of it within this segment): \
+        a criterion partly done here scores its share, e.g. 2 of 5 required problems scores 40. The scores of all \
+        segments are added up"
+    } else {
+        ", from 0 (no evidence) to 100 (clearly met)"
+    };
+
+    format!(
+        "\n\nSuccess criteria for this task:\n{}\n\
+        Score each criterion separately, in the order listed{}, \
+        and give the offsets in seconds of the frames that show the evidence. Score only what the frames show: \
+        a criterion they don't show scores 0. Copy each criterion's text exactly, without its number. Report the scores as \"criteria\": [{{\"criterion\": \"text\", \
+        \"score\": 0-100, \"evidence_seconds\": [offsets], \"note\": \"what the frames show\"}}].",
+        listed.join("\n"),
+        scale
+    )
+}
+
+/// Note placed before each frame with its offset and, when known, its wall-clock time.
+/// Frames standing in for a run of near-identical frames also say how long the run lasted.
+pub fn frame_annotation(frame: &Frame, recording_started_at: Option<DateTime<Local>>) -> String {
+    if !frame.tiles.is_empty() {
+        return format!(
+            "Mosaic of {} frames from {} to {}, left to right then top to bottom. \
+            Each tile is labelled with its offset{}.",
+            frame.tiles.len(),
+            format_hms(frame.offset_seconds),
+            format_hms(frame.last_offset_seconds()),
+            if recording_started_at.is_some() { " and capture time" } else { "" }
+        );
+    }
+
+    let mut note = format!("Frame at {} ({:.0}s)", format_hms(frame.offset_seconds), frame.offset_seconds);
+
+    if let Some(captured_at) = frame.captured_at(recording_started_at) {
+        note.push_str(&format!(", captured {}", captured_at.format("%Y-%m-%d %H:%M:%S")));
+    }
+    note.push('.');
+
+    if let Some(end) = frame.end_offset_seconds {
+        note.push_str(&format!(
+            " It represents {} to {}; the screen did not change noticeably during this time.",
+            format_hms(frame.offset_seconds),
+            format_hms(end)
+        ));
+    }
+
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{IdleSpan, VerificationResult};
+    use crate::verification::test_support::{test_request, test_result};
+
+    #[test]
+    fn test_appeal_carries_previous_verdict_and_justification() {
+        let request = VerificationRequest {
+            appeal: Some(Appeal {
+                previous: VerificationResult {
+                    explanation: "Browser tabs on social media".to_string(),
+                    issues: vec!["Off task from 05:00".to_string(), "Phone visible".to_string()],
+                    ..test_result(false, 80)
+                },
+                justification: "  I was reading the course PDF in the browser ".to_string(),
+            }),
+            ..test_request()
+        };
+
+        let prompt = build_prompt(&request, ResponseFormat::Tool);
+        assert!(prompt.contains("Previous verdict: not verified (confidence 80%, 30.0 minutes on task)"));
+        assert!(prompt.contains("Previous explanation: Browser tabs on social media"));
+        assert!(prompt.contains("Previous issues: Off task from 05:00; Phone visible"));
+        assert!(prompt.contains("User's appeal: \"I was reading the course PDF in the browser\""));
+        assert!(!build_prompt(&test_request(), ResponseFormat::Tool).contains("appealed"));
+    }
+
+    #[test]
+    fn test_idle_spans_are_limited_to_the_segment() {
+        let span = |start_seconds: f64, end_seconds: f64| IdleSpan { start_seconds, end_seconds };
+        let activity = ActivityProfile {
+            sample_interval_seconds: 5,
+            active_seconds: 1500.0,
+            idle_seconds: 300.0,
+            idle_spans: vec![span(60.0, 120.0), span(1000.0, 1100.0), span(1500.0, 1640.0)],
+            webcam_checked: false,
+        };
+        let whole = VerificationRequest { activity: Some(activity), ..test_request() };
+        assert!(build_prompt(&whole, ResponseFormat::Tool)
+            .contains("Idle stretches: 00:01:00-00:02:00, 00:16:40-00:18:20, 00:25:00-00:27:20."));
+
+        let segment = VerificationRequest {
+            window: Some(TimeWindow { index: 1, count: 3, start_seconds: 600.0, end_seconds: 1200.0 }),
+            ..whole
+        };
+        let prompt = build_prompt(&segment, ResponseFormat::Tool);
+        assert!(prompt.contains("segment 2 of 3 (00:10:00-00:20:00)"));
+        assert!(prompt.contains("Idle stretches: 00:16:40-00:18:20."));
+        assert!(!prompt.contains("00:01:00"));
+    }
+
+    #[test]
+    fn test_criteria_are_listed_in_order() {
+        let request = VerificationRequest {
+            criteria: vec!["Solved at least 5 practice problems".to_string(), "Checked the answers".to_string()],
+            ..test_request()
+        };
+
+        let prompt = build_prompt(&request, ResponseFormat::Json);
+        assert!(prompt.contains("Success criteria for this task:\n1. Solved at least 5 practice problems\n2. Checked the answers\n"));
+        assert!(prompt.contains("from 0 (no evidence) to 100 (clearly met)"));
+        assert!(!build_prompt(&test_request(), ResponseFormat::Json).contains("Success criteria"));
+
+        let segment = VerificationRequest {
+            window: Some(TimeWindow { index: 0, count: 2, start_seconds: 0.0, end_seconds: 900.0 }),
+            ..request
+        };
+        assert!(build_prompt(&segment, ResponseFormat::Json).contains("The scores of all segments are added up"));
+    }
+}
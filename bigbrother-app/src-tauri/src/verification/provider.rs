@@ -0,0 +1,151 @@
+use super::anthropic::AnthropicProvider;
+use super::catalog::ModelCache;
+use super::chunking::TimeWindow;
+use super::error::VerificationError;
+use super::frames::Frame;
+use super::http::RetryPolicy;
+use super::mock::MockProvider;
+use super::openai::OpenAiCompatibleProvider;
+use super::usage::TokenUsage;
+use crate::database::models::{ActivityProfile, ModelInfo, PromptTemplate, ProviderKind, VerificationResult, VerificationSettings};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use std::path::Path;
+
+/// Maximum number of images attached to a single model request
+pub const MAX_FRAMES_PER_REQUEST: usize = 20;
+
+/// Everything a provider needs to judge one recording
+#[derive(Debug, Clone)]
+pub struct VerificationRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub required_duration_minutes: i64,
+    pub actual_duration_minutes: f64,
+    pub frame_interval_seconds: u32,
+    pub frames: Vec<Frame>,
+    pub window: Option<TimeWindow>, // set when verifying one segment of a long recording
+    pub template: PromptTemplate,
+    pub appeal: Option<Appeal>, // set when re-verifying after the user appealed a verdict
+    pub recording_started_at: Option<DateTime<Local>>, // wall-clock time of offset 0, when known
+    pub activity: Option<ActivityProfile>, // idle stretches found locally, when analysed
+    pub canary: Option<String>, // check token planted in the system prompt, which must not appear in the answer
+    pub criteria: Vec<String>, // the task's success criteria, to be scored one by one
+}
+
+impl VerificationRequest {
+    /// Recording time covered by the frames that are sent: the first frame's offset to the end
+    /// of the last frame's interval
+    pub fn frame_range(&self) -> Option<(f64, f64)> {
+        let sent = &self.frames[..self.frames.len().min(MAX_FRAMES_PER_REQUEST)];
+        let start = sent.iter().map(|f| f.offset_seconds).reduce(f64::min)?;
+        let end = sent
+            .iter()
+            .map(|f| f.last_offset_seconds() + self.frame_interval_seconds as f64)
+            .reduce(f64::max)?;
+        Some((start, end))
+    }
+}
+
+/// An earlier verdict and the user's written case against it
+#[derive(Debug, Clone)]
+pub struct Appeal {
+    pub previous: VerificationResult,
+    pub justification: String,
+}
+
+/// A backend that turns task details plus frames into a verdict
+#[async_trait]
+pub trait VerificationProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn verify(&self, request: &VerificationRequest) -> Result<VerificationResult, VerificationError>;
+
+    /// Models this backend offers, newest first. `refresh` bypasses the on-disk cache.
+    async fn list_models(&self, refresh: bool) -> Result<Vec<ModelInfo>, VerificationError>;
+
+    /// Model that produced the most recent verdict
+    fn model_used(&self) -> Option<String>;
+
+    /// Drain the token usage of every call made since the last drain, successful or not
+    fn take_usage(&self) -> Vec<TokenUsage> {
+        Vec::new()
+    }
+}
+
+/// Create the provider selected in settings. Model lists are cached in `cache_dir` when given.
+pub fn build_provider(
+    settings: &VerificationSettings,
+    anthropic_api_key: Option<String>,
+    cache_dir: Option<&Path>,
+) -> Result<Box<dyn VerificationProvider>, VerificationError> {
+    let model_cache = |name: &str| cache_dir.map(|dir| ModelCache::new(dir.join(name)));
+
+    match settings.provider {
+        ProviderKind::Anthropic => {
+            let api_key = anthropic_api_key.ok_or(VerificationError::MissingApiKey)?;
+            Ok(Box::new(
+                AnthropicProvider::new(
+                    api_key,
+                    settings.anthropic_base_url.clone(),
+                    RetryPolicy::from_settings(settings),
+                )
+                .with_pinned_model(settings.anthropic_model.clone())
+                .with_model_cache(model_cache("models_anthropic.json")),
+            ))
+        }
+        ProviderKind::OpenaiCompatible => Ok(Box::new(
+            OpenAiCompatibleProvider::new(
+                settings.openai_base_url.clone(),
+                settings.openai_model.clone(),
+                settings.openai_api_key.clone(),
+                RetryPolicy::from_settings(settings),
+            )
+            .with_model_cache(model_cache("models_openai.json")),
+        )),
+        ProviderKind::Mock => Ok(Box::new(MockProvider::new())),
+    }
+}
+
+/// The settings with `model` pinned on the configured provider
+pub fn with_model(settings: &VerificationSettings, model: &str) -> VerificationSettings {
+    let mut pinned = settings.clone();
+    match settings.provider {
+        ProviderKind::Anthropic => pinned.anthropic_model = Some(model.to_string()),
+        ProviderKind::OpenaiCompatible => pinned.openai_model = model.to_string(),
+        ProviderKind::Mock => {}
+    }
+    pinned
+}
+
+/// Join a configured base URL and an API path without doubling slashes
+pub(crate) fn endpoint(base_url: &str, path: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_join() {
+        assert_eq!(endpoint("http://localhost:8080/v1/", "/chat/completions"), "http://localhost:8080/v1/chat/completions");
+        assert_eq!(endpoint("https://api.anthropic.com", "v1/messages"), "https://api.anthropic.com/v1/messages");
+    }
+
+    #[test]
+    fn test_build_provider_from_settings() {
+        let mut settings = VerificationSettings::default();
+        assert!(matches!(
+            build_provider(&settings, None, None),
+            Err(VerificationError::MissingApiKey)
+        ));
+        assert_eq!(build_provider(&settings, Some("key".into()), None).unwrap().name(), "anthropic");
+
+        settings.provider = ProviderKind::OpenaiCompatible;
+        assert_eq!(build_provider(&settings, None, None).unwrap().name(), "openai_compatible");
+
+        settings.provider = ProviderKind::Mock;
+        assert_eq!(build_provider(&settings, None, None).unwrap().name(), "mock");
+    }
+}
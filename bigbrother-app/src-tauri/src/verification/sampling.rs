@@ -0,0 +1,210 @@
+use super::chunking::TimeWindow;
+use super::error::VerificationError;
+use super::frames::{Frame, FrameExtractor};
+use crate::database::models::{SamplingSettings, SamplingStrategyKind};
+
+/// Picks which moments of a window are sent as frames
+pub trait FrameSampler: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Up to `budget` frames from `window` in chronological order. `interval` is the uniform
+    /// spacing that would fit the budget.
+    fn sample(
+        &self,
+        video_path: &str,
+        window: &TimeWindow,
+        interval: u32,
+        budget: usize,
+    ) -> Result<Vec<Frame>, VerificationError>;
+}
+
+/// Create the sampler selected in settings
+pub fn build_sampler(settings: &SamplingSettings) -> Box<dyn FrameSampler> {
+    match settings.strategy {
+        SamplingStrategyKind::Uniform => Box::new(UniformSampler),
+        SamplingStrategyKind::SceneChange => Box::new(SceneChangeSampler { threshold: settings.scene_threshold }),
+        SamplingStrategyKind::Hybrid => Box::new(HybridSampler {
+            threshold: settings.scene_threshold,
+            min_coverage_seconds: settings.min_coverage_seconds,
+        }),
+    }
+}
+
+fn extractor(video_path: &str, window: &TimeWindow, interval: u32) -> FrameExtractor {
+    FrameExtractor::new(video_path.to_string(), interval).with_window(window.start_seconds, window.duration_seconds())
+}
+
+/// One frame every `interval` seconds
+pub struct UniformSampler;
+
+impl FrameSampler for UniformSampler {
+    fn name(&self) -> &str {
+        "uniform"
+    }
+
+    fn sample(
+        &self,
+        video_path: &str,
+        window: &TimeWindow,
+        interval: u32,
+        budget: usize,
+    ) -> Result<Vec<Frame>, VerificationError> {
+        Ok(spread(extractor(video_path, window, interval).extract()?, budget))
+    }
+}
+
+/// The window's first frame plus the frames with the highest scene scores
+pub struct SceneChangeSampler {
+    pub threshold: f64,
+}
+
+impl FrameSampler for SceneChangeSampler {
+    fn name(&self) -> &str {
+        "scene_change"
+    }
+
+    fn sample(
+        &self,
+        video_path: &str,
+        window: &TimeWindow,
+        interval: u32,
+        budget: usize,
+    ) -> Result<Vec<Frame>, VerificationError> {
+        let scenes = extractor(video_path, window, interval).extract_scene_changes(self.threshold)?;
+        Ok(top_scenes(scenes, budget))
+    }
+}
+
+/// A frame at least every `min_coverage_seconds`, then the biggest scene changes with what is left
+/// of the budget
+pub struct HybridSampler {
+    pub threshold: f64,
+    pub min_coverage_seconds: u32,
+}
+
+impl FrameSampler for HybridSampler {
+    fn name(&self) -> &str {
+        "hybrid"
+    }
+
+    fn sample(
+        &self,
+        video_path: &str,
+        window: &TimeWindow,
+        interval: u32,
+        budget: usize,
+    ) -> Result<Vec<Frame>, VerificationError> {
+        let coverage_interval = self.min_coverage_seconds.max(interval);
+        let coverage = extractor(video_path, window, coverage_interval).extract()?;
+        if coverage.len() >= budget {
+            return Ok(spread(coverage, budget));
+        }
+
+        let scenes = extractor(video_path, window, interval).extract_scene_changes(self.threshold)?;
+        Ok(fill_with_changes(coverage, scenes, budget, interval as f64 / 2.0))
+    }
+}
+
+/// `count` frames spread evenly over `frames`, keeping the first
+pub(crate) fn spread(frames: Vec<Frame>, count: usize) -> Vec<Frame> {
+    let total = frames.len();
+    if total <= count {
+        return frames;
+    }
+
+    let mut wanted = (0..count).map(|i| i * total / count).peekable();
+    frames
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| wanted.next_if_eq(idx).is_some())
+        .map(|(_, frame)| frame)
+        .collect()
+}
+
+/// Keep the first frame and the highest-scoring rest, `budget` in all, in chronological order
+fn top_scenes(scenes: Vec<(Frame, f64)>, budget: usize) -> Vec<Frame> {
+    let mut scenes = scenes.into_iter();
+    let Some((first, _)) = scenes.next().filter(|_| budget > 0) else {
+        return Vec::new();
+    };
+
+    let mut ranked: Vec<(Frame, f64)> = scenes.collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(budget - 1);
+
+    let mut frames: Vec<Frame> = std::iter::once(first).chain(ranked.into_iter().map(|(frame, _)| frame)).collect();
+    frames.sort_by(|a, b| a.offset_seconds.total_cmp(&b.offset_seconds));
+    frames
+}
+
+/// Add the highest-scoring scene changes to `coverage` until `budget` frames are reached,
+/// skipping changes within `min_gap_seconds` of a frame already kept
+fn fill_with_changes(coverage: Vec<Frame>, scenes: Vec<(Frame, f64)>, budget: usize, min_gap_seconds: f64) -> Vec<Frame> {
+    let mut frames = coverage;
+    let mut ranked = scenes;
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    for (frame, _) in ranked {
+        if frames.len() >= budget {
+            break;
+        }
+        if frames.iter().all(|kept| (kept.offset_seconds - frame.offset_seconds).abs() >= min_gap_seconds) {
+            frames.push(frame);
+        }
+    }
+
+    frames.sort_by(|a, b| a.offset_seconds.total_cmp(&b.offset_seconds));
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::test_frame;
+    use image::RgbImage;
+
+    fn frame(offset_seconds: f64) -> Frame {
+        test_frame(offset_seconds, &RgbImage::new(1, 1))
+    }
+
+    fn offsets(frames: &[Frame]) -> Vec<f64> {
+        frames.iter().map(|f| f.offset_seconds).collect()
+    }
+
+    #[test]
+    fn test_scene_change_keeps_first_frame_and_top_scores() {
+        let scenes = vec![(frame(0.0), 0.0), (frame(12.0), 0.4), (frame(30.0), 0.9), (frame(41.0), 0.35)];
+
+        assert_eq!(offsets(&top_scenes(scenes.clone(), 3)), vec![0.0, 12.0, 30.0]);
+        assert_eq!(offsets(&top_scenes(scenes.clone(), 10)), vec![0.0, 12.0, 30.0, 41.0]);
+        assert!(top_scenes(scenes, 0).is_empty());
+    }
+
+    #[test]
+    fn test_hybrid_keeps_coverage_and_adds_biggest_changes() {
+        let coverage = vec![frame(0.0), frame(120.0), frame(240.0)];
+        let scenes = vec![(frame(0.0), 0.0), (frame(47.0), 0.5), (frame(122.0), 0.95), (frame(200.0), 0.7), (frame(260.0), 0.4)];
+
+        // 122 is too close to the coverage frame at 120; 260 doesn't fit the budget
+        let frames = fill_with_changes(coverage, scenes, 5, 5.0);
+        assert_eq!(offsets(&frames), vec![0.0, 47.0, 120.0, 200.0, 240.0]);
+    }
+
+    #[test]
+    fn test_spread_keeps_even_coverage() {
+        let frames: Vec<Frame> = (0..10).map(|i| frame(i as f64)).collect();
+        assert_eq!(offsets(&spread(frames, 4)), vec![0.0, 2.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn test_build_sampler_from_settings() {
+        let mut settings = SamplingSettings::default();
+        assert_eq!(build_sampler(&settings).name(), "uniform");
+
+        settings.strategy = SamplingStrategyKind::SceneChange;
+        assert_eq!(build_sampler(&settings).name(), "scene_change");
+
+        settings.strategy = SamplingStrategyKind::Hybrid;
+        assert_eq!(build_sampler(&settings).name(), "hybrid");
+    }
+}
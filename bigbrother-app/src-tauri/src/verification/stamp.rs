@@ -0,0 +1,112 @@
+use super::chunking::format_hms;
+use super::error::VerificationError;
+use super::frames::{decode_rgb, encode_jpeg, Frame};
+use chrono::{DateTime, Local};
+use image::{Rgb, RgbImage};
+
+/// 3x5 glyphs, one row per byte with the leftmost pixel in bit 2. Labels only need digits,
+/// colons and spaces; anything else is drawn as a space.
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        _ => [0; 5],
+    }
+}
+
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+const PADDING: u32 = 2; // font pixels around the text; glyphs are one apart
+
+/// Text burned into a frame: its offset, then the wall-clock time when known
+pub fn frame_label(frame: &Frame, recording_started_at: Option<DateTime<Local>>) -> String {
+    let offset = format_hms(frame.offset_seconds);
+    match frame.captured_at(recording_started_at) {
+        Some(captured_at) => format!("{} {}", offset, captured_at.format("%H:%M:%S")),
+        None => offset,
+    }
+}
+
+/// Draw `text` in white on a black box in the top-left corner, scaled to stay legible
+/// after the vision API downsizes the image
+pub(crate) fn draw_label(img: &mut RgbImage, text: &str) {
+    let scale = (img.width() / 320).max(2);
+    let chars: Vec<char> = text.chars().collect();
+    let box_width = (chars.len() as u32 * (GLYPH_WIDTH + 1) - 1 + 2 * PADDING) * scale;
+    let box_height = (GLYPH_HEIGHT + 2 * PADDING) * scale;
+
+    for y in 0..box_height.min(img.height()) {
+        for x in 0..box_width.min(img.width()) {
+            img.put_pixel(x, y, Rgb([0, 0, 0]));
+        }
+    }
+
+    for (idx, c) in chars.into_iter().enumerate() {
+        let left = PADDING + idx as u32 * (GLYPH_WIDTH + 1);
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = (left + col) * scale + dx;
+                        let y = (PADDING + row as u32) * scale + dy;
+                        if x < img.width() && y < img.height() {
+                            img.put_pixel(x, y, Rgb([255, 255, 255]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Burn the frame's offset and capture time into its top-left corner
+pub fn stamp_frame(frame: &mut Frame, recording_started_at: Option<DateTime<Local>>) -> Result<(), VerificationError> {
+    let mut img = decode_rgb(frame)?;
+
+    draw_label(&mut img, &frame_label(frame, recording_started_at));
+    frame.data = encode_jpeg(&img)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::test_support::test_frame;
+    use chrono::TimeZone;
+
+    fn frame(offset_seconds: f64) -> Frame {
+        test_frame(offset_seconds, &RgbImage::from_pixel(640, 360, Rgb([200, 200, 200])))
+    }
+
+    #[test]
+    fn test_label_has_offset_and_wall_clock() {
+        let started = Local.with_ymd_and_hms(2024, 10, 17, 14, 30, 0).unwrap();
+
+        assert_eq!(frame_label(&frame(150.0), Some(started)), "00:02:30 14:32:30");
+        assert_eq!(frame_label(&frame(3725.0), None), "01:02:05");
+    }
+
+    #[test]
+    fn test_stamp_darkens_corner_only() {
+        let mut stamped = frame(150.0);
+        stamp_frame(&mut stamped, None).unwrap();
+
+        let img = decode_rgb(&stamped).unwrap();
+        assert_eq!((img.width(), img.height()), (640, 360));
+        assert!(img.get_pixel(1, 1)[0] < 60, "label background should be dark");
+        assert!(img.get_pixel(600, 300)[0] > 150, "rest of the frame should be untouched");
+    }
+}
@@ -0,0 +1,136 @@
+use super::provider::VerificationRequest;
+use crate::database::models::PromptTemplate;
+
+/// Template used when no stored template matches the task type
+pub const DEFAULT_TEMPLATE_NAME: &str = "default";
+
+/// Version recorded for the built-in template; stored templates start at 1
+pub const BUILTIN_TEMPLATE_VERSION: i64 = 0;
+
+/// Placeholders a template may use, written as `{{name}}`
+pub const PLACEHOLDERS: &[&str] = &[
+    "title",
+    "description",
+    "required_minutes",
+    "video_minutes",
+    "frame_interval",
+];
+
+const BUILTIN_BODY: &str = "You are verifying a productivity task completion.
+
+Task Details:
+- Title: {{title}}
+- Description: {{description}}
+- Required Duration: {{required_minutes}} minutes
+- Video Duration: {{video_minutes}} minutes
+
+Analyze the provided video frames (1 frame every {{frame_interval}} seconds) and determine:
+1. Was the user engaged in the described task?
+2. For what percentage of the video was the task being performed?
+3. Did they meet the minimum duration requirement?
+4. Were there significant distractions or off-task behavior?";
+
+/// The template compiled into the app
+pub fn builtin_template() -> PromptTemplate {
+    PromptTemplate {
+        id: None,
+        name: DEFAULT_TEMPLATE_NAME.to_string(),
+        version: BUILTIN_TEMPLATE_VERSION,
+        body: BUILTIN_BODY.to_string(),
+        created_at: None,
+    }
+}
+
+fn placeholder_value(name: &str, request: &VerificationRequest) -> Option<String> {
+    Some(match name {
+        "title" => request.title.clone(),
+        "description" => request.description.clone().unwrap_or_else(|| "N/A".to_string()),
+        "required_minutes" => request.required_duration_minutes.to_string(),
+        "video_minutes" => format!("{:.1}", request.actual_duration_minutes),
+        "frame_interval" => request.frame_interval_seconds.to_string(),
+        _ => return None,
+    })
+}
+
+/// Every `{{name}}` in the body, in order of appearance
+fn placeholders(body: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    let mut rest = 0;
+    std::iter::from_fn(move || {
+        let start = rest + body[rest..].find("{{")?;
+        let end = start + 2 + body[start + 2..].find("}}")?;
+        rest = end + 2;
+        Some((start, end + 2, body[start + 2..end].trim()))
+    })
+}
+
+/// Placeholders in `body` that `render` would not know how to fill
+pub fn unknown_placeholders(body: &str) -> Vec<String> {
+    placeholders(body)
+        .filter(|(_, _, name)| !PLACEHOLDERS.contains(name))
+        .map(|(_, _, name)| name.to_string())
+        .collect()
+}
+
+/// Fill the template's placeholders from the request. Unknown placeholders are left as written.
+pub fn render(template: &PromptTemplate, request: &VerificationRequest) -> String {
+    let body = &template.body;
+    let mut rendered = String::with_capacity(body.len());
+    let mut copied = 0;
+
+    for (start, end, name) in placeholders(body) {
+        if let Some(value) = placeholder_value(name, request) {
+            rendered.push_str(&body[copied..start]);
+            rendered.push_str(&value);
+            copied = end;
+        }
+    }
+
+    rendered.push_str(&body[copied..]);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> VerificationRequest {
+        VerificationRequest {
+            title: "Run 5k".to_string(),
+            description: None,
+            required_duration_minutes: 30,
+            actual_duration_minutes: 32.5,
+            frame_interval_seconds: 10,
+            frames: vec![],
+            window: None,
+            template: builtin_template(),
+            appeal: None,
+            recording_started_at: None,
+            activity: None,
+            canary: None,
+            criteria: vec![],
+        }
+    }
+
+    #[test]
+    fn test_builtin_template_fills_every_placeholder() {
+        let prompt = render(&builtin_template(), &request());
+
+        assert!(prompt.contains("- Title: Run 5k"));
+        assert!(prompt.contains("- Description: N/A"));
+        assert!(prompt.contains("Video Duration: 32.5 minutes"));
+        assert!(prompt.contains("1 frame every 10 seconds"));
+        assert!(!prompt.contains("{{"));
+        assert!(unknown_placeholders(BUILTIN_BODY).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_placeholders_are_reported_and_kept() {
+        let template = PromptTemplate {
+            body: "Exercise: {{ title }} for {{required_minutes}} min, pace {{pace}}".to_string(),
+            ..builtin_template()
+        };
+
+        assert_eq!(unknown_placeholders(&template.body), vec!["pace"]);
+        assert_eq!(render(&template, &request()), "Exercise: Run 5k for 30 min, pace {{pace}}");
+    }
+}
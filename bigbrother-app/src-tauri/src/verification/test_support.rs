@@ -0,0 +1,87 @@
+use super::frames::{encode_jpeg, Frame};
+use super::provider::VerificationRequest;
+use super::template::builtin_template;
+use crate::database::models::VerificationResult;
+use image::{Rgb, RgbImage};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A model answer with 30 minutes on task and nothing else to report; tests override the fields
+/// they care about with struct update syntax
+pub fn test_result(verified: bool, confidence: i64) -> VerificationResult {
+    VerificationResult {
+        verified,
+        confidence,
+        time_on_task_minutes: 30.0,
+        explanation: String::new(),
+        issues: vec![],
+        timeline: vec![],
+        criteria: vec![],
+    }
+}
+
+/// A request for a 30-minute essay task with the built-in template and no frames
+pub fn test_request() -> VerificationRequest {
+    VerificationRequest {
+        title: "Essay".to_string(),
+        description: None,
+        required_duration_minutes: 30,
+        actual_duration_minutes: 20.0,
+        frame_interval_seconds: 10,
+        frames: vec![],
+        window: None,
+        template: builtin_template(),
+        appeal: None,
+        recording_started_at: None,
+        activity: None,
+        canary: None,
+        criteria: vec![],
+    }
+}
+
+/// A frame at `offset_seconds` showing `img`
+pub fn test_frame(offset_seconds: f64, img: &RgbImage) -> Frame {
+    Frame {
+        offset_seconds,
+        width: img.width(),
+        height: img.height(),
+        data: encode_jpeg(img).unwrap(),
+        end_offset_seconds: None,
+        tiles: Vec::new(),
+    }
+}
+
+/// A 160x90 frame with a vertical edge at `split` (out of 160 columns)
+pub fn edge_frame(offset_seconds: f64, split: u32) -> Frame {
+    let img = RgbImage::from_fn(160, 90, |x, _| if x < split { Rgb([230, 230, 230]) } else { Rgb([20, 20, 20]) });
+    test_frame(offset_seconds, &img)
+}
+
+pub fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg").arg("-version").output().is_ok()
+}
+
+/// Generate a short lavfi test clip, returning None when FFmpeg isn't installed
+pub fn test_clip(duration: u32, size: &str) -> Option<PathBuf> {
+    if !ffmpeg_available() {
+        eprintln!("ffmpeg not available, skipping");
+        return None;
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "bigbrother_test_{}_{}_{}.mp4",
+        std::process::id(),
+        duration,
+        size
+    ));
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error", "-y", "-f", "lavfi", "-i"])
+        .arg(format!("testsrc=duration={}:size={}:rate=5", duration, size))
+        .args(["-pix_fmt", "yuv420p"])
+        .arg(&path)
+        .status()
+        .expect("failed to run ffmpeg");
+    assert!(status.success());
+
+    Some(path)
+}
@@ -0,0 +1,179 @@
+use serde_json::{json, Value};
+
+pub const REPORT_TOOL_NAME: &str = "report_verification";
+
+/// Tool definition whose input schema mirrors `VerificationResult`, `TimelineEntry` and `CriterionScore`.
+/// Fields added to the result must be added here as well.
+pub fn report_verification_tool() -> Value {
+    json!({
+        "name": REPORT_TOOL_NAME,
+        "description": "Report whether the recording shows the user completing the described task.",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "verified": {
+                    "type": "boolean",
+                    "description": "True if the user genuinely worked on the task"
+                },
+                "confidence": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 100,
+                    "description": "Confidence in the verdict, 0-100"
+                },
+                "time_on_task_minutes": {
+                    "type": "number",
+                    "minimum": 0,
+                    "description": "Minutes of the recording spent on the task"
+                },
+                "explanation": {
+                    "type": "string",
+                    "description": "Detailed explanation of the verdict"
+                },
+                "issues": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Distractions, off-task behaviour or other concerns"
+                },
+                "timeline": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "start_seconds": {
+                                "type": "number",
+                                "minimum": 0,
+                                "description": "Start of the stretch, in seconds from the start of the recording"
+                            },
+                            "end_seconds": {
+                                "type": "number",
+                                "minimum": 0,
+                                "description": "End of the stretch, in seconds from the start of the recording"
+                            },
+                            "category": {
+                                "type": "string",
+                                "enum": ["on_task", "off_task", "idle", "break"]
+                            },
+                            "activity": {
+                                "type": "string",
+                                "description": "What the user was doing"
+                            },
+                            "label": {
+                                "type": ["string", "null"],
+                                "description": "App or website in the foreground, if recognisable"
+                            }
+                        },
+                        "required": ["start_seconds", "end_seconds", "category", "activity", "label"]
+                    }
+                },
+                "criteria": {
+                    "type": "array",
+                    "description": "One score per success criterion of the task, in the order listed; empty if it has none",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "criterion": {
+                                "type": "string",
+                                "description": "The criterion being scored, copied exactly as listed"
+                            },
+                            "score": {
+                                "type": "integer",
+                                "minimum": 0,
+                                "maximum": 100,
+                                "description": "0 for no evidence, 100 for clearly met"
+                            },
+                            "evidence_seconds": {
+                                "type": "array",
+                                "items": { "type": "number", "minimum": 0 },
+                                "description": "Offsets in seconds of the frames that show the evidence"
+                            },
+                            "note": {
+                                "type": "string",
+                                "description": "What the frames show for this criterion"
+                            }
+                        },
+                        "required": ["criterion", "score", "evidence_seconds", "note"]
+                    }
+                }
+            },
+            "required": ["verified", "confidence", "time_on_task_minutes", "explanation", "issues", "timeline", "criteria"]
+        }
+    })
+}
+
+/// Force the model to answer through the report tool
+pub fn forced_tool_choice() -> Value {
+    json!({ "type": "tool", "name": REPORT_TOOL_NAME })
+}
+
+/// Find the report tool call in a Messages API response, returning its block
+pub fn find_tool_use(response_json: &Value) -> Option<&Value> {
+    response_json["content"]
+        .as_array()?
+        .iter()
+        .find(|block| block["type"] == "tool_use" && block["name"] == REPORT_TOOL_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{CriterionScore, TimelineEntry, VerificationResult};
+
+    #[test]
+    fn test_schema_matches_verification_result() {
+        let result = VerificationResult {
+            verified: true,
+            confidence: 1,
+            time_on_task_minutes: 1.0,
+            explanation: String::new(),
+            issues: vec![],
+            timeline: vec![TimelineEntry::default()],
+            criteria: vec![CriterionScore::default()],
+        };
+        let value = serde_json::to_value(&result).unwrap();
+        let tool = report_verification_tool();
+        let schema = &tool["input_schema"];
+
+        let mut result_fields: Vec<&String> = value.as_object().unwrap().keys().collect();
+        let mut schema_fields: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        result_fields.sort();
+        schema_fields.sort();
+        assert_eq!(result_fields, schema_fields);
+        assert_eq!(schema["required"].as_array().unwrap().len(), result_fields.len());
+
+        let mut entry_fields: Vec<&String> = value["timeline"][0].as_object().unwrap().keys().collect();
+        let mut entry_schema: Vec<&String> = schema["properties"]["timeline"]["items"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        entry_fields.sort();
+        entry_schema.sort();
+        assert_eq!(entry_fields, entry_schema);
+
+        let mut criterion_fields: Vec<&String> = value["criteria"][0].as_object().unwrap().keys().collect();
+        let mut criterion_schema: Vec<&String> = schema["properties"]["criteria"]["items"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        criterion_fields.sort();
+        criterion_schema.sort();
+        assert_eq!(criterion_fields, criterion_schema);
+    }
+
+    #[test]
+    fn test_find_tool_use() {
+        let response = json!({
+            "content": [
+                {"type": "text", "text": "Let me report."},
+                {"type": "tool_use", "id": "toolu_1", "name": "report_verification", "input": {"verified": true}}
+            ]
+        });
+
+        let block = find_tool_use(&response).unwrap();
+        assert_eq!(block["id"], "toolu_1");
+        assert_eq!(block["input"]["verified"], true);
+        assert!(find_tool_use(&json!({"content": [{"type": "text", "text": "{}"}]})).is_none());
+    }
+}
@@ -0,0 +1,89 @@
+use super::pricing::model_price;
+use std::sync::Mutex;
+
+/// Tokens billed for one model call
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenUsage {
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+impl TokenUsage {
+    /// Read the `usage` block of a Messages API response
+    pub fn from_anthropic(model: &str, response_json: &serde_json::Value) -> Option<Self> {
+        let usage = &response_json["usage"];
+        // Cache reads and writes are billed as input too
+        let input_tokens = usage["input_tokens"].as_i64()?
+            + usage["cache_creation_input_tokens"].as_i64().unwrap_or(0)
+            + usage["cache_read_input_tokens"].as_i64().unwrap_or(0);
+
+        Some(Self {
+            model: response_json["model"].as_str().unwrap_or(model).to_string(),
+            input_tokens,
+            output_tokens: usage["output_tokens"].as_i64().unwrap_or(0),
+        })
+    }
+
+    /// Read the `usage` block of a chat completion; local servers often leave it out
+    pub fn from_openai(model: &str, response_json: &serde_json::Value) -> Option<Self> {
+        let usage = &response_json["usage"];
+
+        Some(Self {
+            model: model.to_string(),
+            input_tokens: usage["prompt_tokens"].as_i64()?,
+            output_tokens: usage["completion_tokens"].as_i64().unwrap_or(0),
+        })
+    }
+
+    pub fn cost_usd(&self) -> f64 {
+        model_price(&self.model).cost_usd(self.input_tokens, self.output_tokens)
+    }
+}
+
+/// Calls made by a provider since the last `take`, including ones whose reply was unusable
+#[derive(Debug, Default)]
+pub struct UsageLog(Mutex<Vec<TokenUsage>>);
+
+impl UsageLog {
+    pub fn record(&self, usage: Option<TokenUsage>) {
+        if let Some(usage) = usage {
+            self.0.lock().unwrap_or_else(|e| e.into_inner()).push(usage);
+        }
+    }
+
+    pub fn take(&self) -> Vec<TokenUsage> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reads_anthropic_usage() {
+        let response = json!({
+            "model": "claude-3-5-sonnet-20241022",
+            "usage": {"input_tokens": 12000, "output_tokens": 640, "cache_read_input_tokens": 100}
+        });
+
+        let usage = TokenUsage::from_anthropic("requested-model", &response).unwrap();
+        assert_eq!(usage.model, "claude-3-5-sonnet-20241022");
+        assert_eq!(usage.input_tokens, 12100);
+        assert_eq!(usage.output_tokens, 640);
+        assert!(usage.cost_usd() > 0.0);
+        assert!(TokenUsage::from_anthropic("m", &json!({})).is_none());
+    }
+
+    #[test]
+    fn test_log_drains() {
+        let log = UsageLog::default();
+        log.record(TokenUsage::from_openai("llava", &json!({"usage": {"prompt_tokens": 5, "completion_tokens": 2}})));
+        log.record(None);
+
+        assert_eq!(log.take().len(), 1);
+        assert!(log.take().is_empty());
+    }
+}
@@ -13,6 +13,7 @@ export default function CreateTaskModal({ isOpen, onClose }: CreateTaskModalProp
   const [dueDate, setDueDate] = useState('');
   const [dueTime, setDueTime] = useState('');
   const [minDuration, setMinDuration] = useState('30');
+  const [criteria, setCriteria] = useState('');
 
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -40,14 +41,16 @@ export default function CreateTaskModal({ isOpen, onClose }: CreateTaskModalProp
     }
 
     const minDurationSeconds = durationMinutes * 60;
+    const criteriaList = criteria.split('\n').map(c => c.trim()).filter(c => c.length > 0);
 
     try {
-      await createTask(title, description || null, dueDateTimeStr, minDurationSeconds);
+      await createTask(title, description || null, dueDateTimeStr, minDurationSeconds, null, criteriaList);
       setTitle('');
       setDescription('');
       setDueDate('');
       setDueTime('');
       setMinDuration('30');
+      setCriteria('');
       onClose();
     } catch (error) {
       alert(`Failed to create task: ${error}`);
@@ -122,6 +125,18 @@ export default function CreateTaskModal({ isOpen, onClose }: CreateTaskModalProp
             <p className="text-xs text-gray-500 mt-1">Maximum 4 hours (240 minutes)</p>
           </div>
 
+          <div>
+            <label className="label">Success Criteria</label>
+            <textarea
+              value={criteria}
+              onChange={(e) => setCriteria(e.target.value)}
+              className="input"
+              rows={3}
+              placeholder={'e.g., Solved at least 5 practice problems\nIDE visible with tests passing'}
+            />
+            <p className="text-xs text-gray-500 mt-1">One per line. Each is scored separately during verification.</p>
+          </div>
+
           <div className="flex gap-3 mt-6">
             <button type="button" onClick={onClose} className="btn btn-secondary flex-1">
               Cancel
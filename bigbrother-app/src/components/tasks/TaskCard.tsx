@@ -34,6 +34,7 @@ export default function TaskCard({ task, onStartRecording }: TaskCardProps) {
           task.status === 'pending' ? 'bg-yellow-100 dark:bg-yellow-900 text-yellow-800 dark:text-yellow-200' :
           task.status === 'in_progress' ? 'bg-blue-100 dark:bg-blue-900 text-blue-800 dark:text-blue-200' :
           task.status === 'completed' ? 'bg-green-100 dark:bg-green-900 text-green-800 dark:text-green-200' :
+          task.status === 'needs_review' ? 'bg-orange-100 dark:bg-orange-900 text-orange-800 dark:text-orange-200' :
           'bg-red-100 dark:bg-red-900 text-red-800 dark:text-red-200'
         }`}>
           {task.status.replace('_', ' ')}
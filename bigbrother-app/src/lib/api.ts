@@ -1,10 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Task, RecordingStatus, VerificationResult, Verification, CostEstimate } from './types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { Task, RecordingStatus, VerificationOutcome, Verification, CostEstimate, VerificationSettings, SpendSummary, BudgetStatus, VerificationJob, JobEvent, BudgetWarning, ModelInfo, PromptTemplate, TimelineEntry, TimelineTotal, InjectionEvent } from './types';
 
 // Task APIs
 export const taskApi = {
-  create: (title: string, description: string | null, due_date: string, min_duration: number): Promise<Task> =>
-    invoke('create_task', { title, description, dueDate: due_date, minDuration: min_duration }),
+  create: (title: string, description: string | null, due_date: string, min_duration: number, task_type?: string | null, criteria?: string[]): Promise<Task> =>
+    invoke('create_task', { title, description, dueDate: due_date, minDuration: min_duration, taskType: task_type ?? null, criteria: criteria ?? null }),
 
   getAll: (): Promise<Task[]> =>
     invoke('get_all_tasks'),
@@ -54,17 +55,46 @@ export const recordingApi = {
 
 // Verification APIs
 export const verificationApi = {
-  verify: (taskId: number): Promise<VerificationResult> =>
-    invoke('verify_task_with_claude', { taskId }),
+  verify: (taskId: number, overrideBudget?: boolean): Promise<VerificationOutcome> =>
+    invoke('verify_task_with_claude', { taskId, overrideBudget }),
 
   getStatus: (taskId: number): Promise<Verification | null> =>
     invoke('get_verification_status', { taskId }),
 
-  getCostEstimate: (videoDuration: number): Promise<CostEstimate> =>
-    invoke('get_verification_cost_estimate', { videoDuration }),
+  getHistory: (taskId: number): Promise<Verification[]> =>
+    invoke('get_verification_history', { taskId }),
+
+  appeal: (verificationId: number, justification: string, overrideBudget?: boolean): Promise<VerificationOutcome> =>
+    invoke('appeal_verification', { verificationId, justification, overrideBudget }),
+
+  getCostEstimate: (
+    videoDuration: number,
+    frameWidth?: number,
+    frameHeight?: number,
+    model?: string
+  ): Promise<CostEstimate> =>
+    invoke('get_verification_cost_estimate', { videoDuration, frameWidth, frameHeight, model }),
 
   extractFrames: (videoPath: string, intervalSeconds: number): Promise<string[]> =>
     invoke('extract_video_frames', { videoPath, intervalSeconds }),
+
+  listModels: (refresh?: boolean): Promise<ModelInfo[]> =>
+    invoke('list_verification_models', { refresh }),
+};
+
+// Timeline APIs
+export const timelineApi = {
+  get: (verificationId: number): Promise<TimelineEntry[]> =>
+    invoke('get_verification_timeline', { verificationId }),
+
+  getTotals: (days?: number, taskId?: number): Promise<TimelineTotal[]> =>
+    invoke('get_timeline_totals', { days, taskId }),
+};
+
+// Prompt injection APIs
+export const injectionApi = {
+  get: (verificationId: number): Promise<InjectionEvent[]> =>
+    invoke('get_injection_events', { verificationId }),
 };
 
 // Settings APIs
@@ -74,4 +104,55 @@ export const settingsApi = {
 
   getClaudeApiKey: (): Promise<string | null> =>
     invoke('get_claude_api_key'),
+
+  getVerificationSettings: (): Promise<VerificationSettings> =>
+    invoke('get_verification_settings'),
+
+  updateVerificationSettings: (settings: VerificationSettings): Promise<VerificationSettings> =>
+    invoke('update_verification_settings', { settings }),
+};
+
+// Prompt template APIs
+export const templatesApi = {
+  list: (): Promise<PromptTemplate[]> =>
+    invoke('list_prompt_templates'),
+
+  get: (name: string, version?: number): Promise<PromptTemplate | null> =>
+    invoke('get_prompt_template', { name, version }),
+
+  save: (name: string, body: string): Promise<PromptTemplate> =>
+    invoke('save_prompt_template', { name, body }),
+};
+
+// Spend APIs
+export const usageApi = {
+  getSpendByDay: (days?: number): Promise<SpendSummary[]> =>
+    invoke('get_spend_by_day', { days }),
+
+  getSpendByMonth: (months?: number): Promise<SpendSummary[]> =>
+    invoke('get_spend_by_month', { months }),
+
+  getSpendByTask: (): Promise<SpendSummary[]> =>
+    invoke('get_spend_by_task'),
+
+  getBudgetStatus: (): Promise<BudgetStatus> =>
+    invoke('get_budget_status'),
+};
+
+// Verification job APIs
+export const jobsApi = {
+  queue: (taskId: number, overrideBudget?: boolean): Promise<VerificationJob> =>
+    invoke('queue_verification', { taskId, overrideBudget }),
+
+  cancel: (jobId: number): Promise<void> =>
+    invoke('cancel_verification', { jobId }),
+
+  list: (taskId?: number): Promise<VerificationJob[]> =>
+    invoke('list_verification_jobs', { taskId }),
+
+  onJobUpdate: (handler: (event: JobEvent) => void): Promise<UnlistenFn> =>
+    listen<JobEvent>('verification-job', (event) => handler(event.payload)),
+
+  onBudgetWarning: (handler: (warning: BudgetWarning) => void): Promise<UnlistenFn> =>
+    listen<BudgetWarning>('verification-budget-warning', (event) => handler(event.payload)),
 };
@@ -5,10 +5,12 @@ export interface Task {
   description?: string;
   due_date: string; // ISO 8601 format
   min_duration: number; // in seconds
-  status: 'pending' | 'in_progress' | 'completed' | 'failed';
+  status: 'pending' | 'in_progress' | 'completed' | 'failed' | 'needs_review';
   video_path?: string;
   created_at?: string;
   updated_at?: string;
+  task_type?: string; // selects the prompt template, e.g. 'coding'
+  criteria: string[]; // success criteria the verdict is scored against, in order
 }
 
 export interface Recording {
@@ -19,6 +21,31 @@ export interface Recording {
   end_time?: string;
   file_path: string;
   status: 'recording' | 'paused' | 'completed' | 'processing';
+  webcam_region?: string; // JSON VideoRegion of the webcam tile in the combined video
+  activity_profile?: string; // JSON ActivityProfile from the last local analysis
+  fingerprint?: string; // JSON screen hashes, compared against later recordings to spot replays
+}
+
+// Part of a video frame, as fractions of its width and height
+export interface VideoRegion {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface IdleSpan {
+  start_seconds: number;
+  end_seconds: number;
+}
+
+// Active and idle time found by local frame differencing, before any API call
+export interface ActivityProfile {
+  sample_interval_seconds: number;
+  active_seconds: number;
+  idle_seconds: number;
+  idle_spans: IdleSpan[];
+  webcam_checked: boolean; // false when the recording has no webcam tile
 }
 
 export interface RecordingStatus {
@@ -36,6 +63,22 @@ export interface Verification {
   ai_confidence?: number;
   time_on_task?: number; // in seconds
   explanation?: string;
+  video_duration?: number; // measured recording length in seconds
+  frames_sent?: number;
+  frames_dropped?: number; // near-duplicate frames removed before upload
+  model?: string; // model that produced the verdict
+  prompt_template?: string;
+  prompt_version?: number;
+  parent_verification_id?: number; // verdict this one re-examined on appeal
+  appeal?: string; // the user's justification
+  verdict?: Verdict;
+  policy_decision?: string; // JSON PolicyDecision
+  sampling?: string; // JSON SamplingSettings the frames were picked with
+  stage?: CascadeStage; // unset for a single-model verification
+  cost_usd?: number; // API spend of this verdict's requests
+  triage_verification_id?: number; // triage verdict an escalation took over from
+  consensus_id?: number; // combined verdict this sample was counted in
+  sample_index?: number; // position among the consensus samples
   verified_at?: string;
 }
 
@@ -46,14 +89,213 @@ export interface VerificationResult {
   explanation: string;
   issues: string[];
   timeline: TimelineEntry[];
+  criteria: CriterionScore[]; // one per task criterion, in the task's order
+}
+
+export interface CriterionScore {
+  criterion: string;
+  score: number; // 0 for no evidence, 100 for clearly met
+  evidence_seconds: number[]; // offsets of the frames that show it
+  note: string;
+}
+
+export type Verdict = 'pass' | 'fail' | 'needs_review';
+
+export interface VerificationPolicy {
+  min_confidence: number; // below this the verdict is needs_review
+  min_time_on_task_ratio: number; // share of the task's min_duration that must be on task
+  max_issues?: number | null; // more issues than this fails; unset ignores the count
+  min_criterion_score: number; // every success criterion must score at least this to pass
+}
+
+export interface PolicyDecision {
+  verdict: Verdict;
+  reasons: string[];
+  inputs: {
+    model_verified: boolean;
+    confidence: number;
+    time_on_task_minutes: number;
+    required_minutes: number;
+    issue_count: number;
+    review_flags: string[]; // local findings that keep a pass from being final
+    vetoes: string[]; // local findings that fail the verdict outright
+    criterion_scores: number[]; // in the task's order; empty when the task has no criteria
+    policy: VerificationPolicy;
+  };
+}
+
+export interface VerificationOutcome {
+  verification_id: number;
+  result: VerificationResult;
+  decision: PolicyDecision;
 }
 
+export type ActivityCategory = 'on_task' | 'off_task' | 'idle' | 'break';
+
 export interface TimelineEntry {
-  timestamp: string;
+  start_seconds: number; // offset from the start of the recording
+  end_seconds: number;
+  category: ActivityCategory;
   activity: string;
+  label?: string | null; // app or site in focus
+}
+
+// on_screen_instructions: OCR found text addressed to the model; model_reported: the model flagged an attempt;
+// canary_leaked: the answer contains the system prompt's check token; quoted_injection: the answer repeats on-screen instructions
+export type InjectionEventKind = 'on_screen_instructions' | 'model_reported' | 'canary_leaked' | 'quoted_injection';
+
+export interface InjectionEvent {
+  kind: InjectionEventKind;
+  offset_seconds?: number | null; // frame the text was seen in
+  detail: string;
+}
+
+export interface TimelineTotal {
+  category: ActivityCategory;
+  label?: string | null;
+  total_seconds: number;
+  sessions: number; // verifications the time was seen in
+}
+
+export interface PromptTemplate {
+  id?: number;
+  name: string; // task type it applies to, or 'default'
+  version: number; // 0 is the built-in template
+  body: string; // text with {{placeholder}} task fields
+  created_at?: string;
+}
+
+export interface ModelInfo {
+  id: string;
+  display_name?: string;
+  created_at?: string;
 }
 
 export interface CostEstimate {
   estimated_tokens: number;
   estimated_cost_usd: number;
+  model: string;
+  frames: number;
+}
+
+export interface BudgetStatus {
+  month_spent_usd: number;
+  monthly_budget_usd?: number;
+  remaining_usd?: number;
+  max_verification_cost_usd?: number;
+}
+
+export interface BudgetWarning {
+  month_spent_usd: number;
+  monthly_budget_usd: number;
+}
+
+export type BudgetLimit = 'monthly' | 'per_verification';
+
+// Error thrown by verificationApi.verify
+export type VerifyError =
+  | { kind: 'budget_exceeded'; limit: BudgetLimit; projected_usd: number; remaining_usd: number; message: string }
+  | { kind: 'failed'; message: string };
+
+export type JobState = 'queued' | 'extracting' | 'uploading' | 'parsing' | 'done' | 'failed';
+
+export interface VerificationJob {
+  id: number;
+  task_id: number;
+  state: JobState;
+  override_budget: boolean;
+  attempts: number;
+  error?: string;
+  verification_id?: number; // set once the job is done
+  parent_verification_id?: number; // set for appeals
+  appeal?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface TimeWindow {
+  index: number;
+  count: number;
+  start_seconds: number;
+  end_seconds: number;
+}
+
+// Payload of the 'verification-job' event
+export interface JobEvent {
+  job: VerificationJob;
+  window?: TimeWindow; // segment being worked on, for chunked verifications
+}
+
+export interface SpendSummary {
+  period: string; // "2024-10-17", "2024-10" or the task title
+  task_id?: number; // set when grouped by task
+  requests: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost_usd: number;
+}
+
+export type ProviderKind = 'anthropic' | 'openai_compatible' | 'mock';
+
+// full frames, labelled 3x3 grids of frames, or grids plus full frames where the screen changed
+export type FrameMode = 'full' | 'mosaic' | 'mixed';
+
+// uniform: fixed interval; scene_change: where the picture changes; hybrid: minimum coverage plus biggest changes
+export type SamplingStrategyKind = 'uniform' | 'scene_change' | 'hybrid';
+
+export interface SamplingSettings {
+  strategy: SamplingStrategyKind;
+  scene_threshold: number; // 0-1 scene score a frame must exceed to count as a change
+  min_coverage_seconds: number; // hybrid: at least one frame this often
+}
+
+// off: skip the idle analysis; flag: verify anyway but hold back a pass; reject: fail without calling the API
+export type IdleAction = 'off' | 'flag' | 'reject';
+
+// what to do when replayed footage or a frozen webcam is found; veto fails the verdict
+export type CheatAction = 'off' | 'flag' | 'veto';
+
+// triage: cheap model, final; escalated: uncertain triage verdict handed on; escalation: main model's verdict
+export type CascadeStage = 'triage' | 'escalated' | 'escalation';
+
+export interface CascadeSettings {
+  enabled: boolean;
+  triage_model: string; // model of the configured provider used for the first pass
+  triage_max_dimension: number; // longest edge of triage frames, in pixels
+  uncertainty_min: number; // triage confidence from uncertainty_min to uncertainty_max escalates
+  uncertainty_max: number;
+}
+
+// sampling: same model, frames taken at shifted moments; models: same frames, one model per sample
+export type SampleVariation = 'sampling' | 'models';
+
+export interface ConsensusSettings {
+  samples: number; // 1 turns consensus off
+  variation: SampleVariation;
+  models: string[]; // models: used in turn, one per sample
+}
+
+export interface VerificationSettings {
+  provider: ProviderKind;
+  anthropic_base_url: string;
+  anthropic_model?: string | null; // pinned model, unset picks the newest Sonnet/Opus
+  openai_base_url: string;
+  openai_model: string;
+  openai_api_key?: string | null;
+  chunk_minutes: number; // long recordings are verified in windows of this length
+  dedup_threshold: number; // max differing hash bits for frames to count as duplicates, 0 disables
+  burn_in_timestamps: boolean; // draw each frame's offset and capture time onto the image
+  frame_mode: FrameMode;
+  sampling: SamplingSettings;
+  idle_action: IdleAction;
+  max_idle_ratio: number; // share of the recording that may be idle before idle_action applies
+  cheat_action: CheatAction;
+  ocr_frames: boolean; // read on-screen text with Tesseract, when installed, to spot prompt injection
+  cascade: CascadeSettings;
+  consensus: ConsensusSettings;
+  monthly_budget_usd?: number; // unset means no cap
+  max_verification_cost_usd?: number; // unset means no cap
+  max_retries: number; // retries for rate limits, overload and network errors
+  request_timeout_seconds: number;
+  policy: VerificationPolicy;
 }
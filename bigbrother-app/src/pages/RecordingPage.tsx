@@ -5,8 +5,8 @@ import { useTaskStore } from '../store/taskStore';
 import RecordingControls from '../components/recording/RecordingControls';
 import RecordingIndicator from '../components/recording/RecordingIndicator';
 import DurationDisplay from '../components/recording/DurationDisplay';
-import { verificationApi } from '../lib/api';
-import type { Task } from '../lib/types';
+import { jobsApi, verificationApi } from '../lib/api';
+import type { JobEvent, Task, VerifyError } from '../lib/types';
 
 export default function RecordingPage() {
   const { taskId } = useParams<{ taskId: string }>();
@@ -17,6 +17,7 @@ export default function RecordingPage() {
   const [isVerifying, setIsVerifying] = useState(false);
   const [verificationComplete, setVerificationComplete] = useState(false);
   const [isStopping, setIsStopping] = useState(false);
+  const [jobProgress, setJobProgress] = useState<JobEvent | null>(null);
 
   useEffect(() => {
     if (taskId) {
@@ -27,6 +28,19 @@ export default function RecordingPage() {
     }
   }, [taskId, tasks]);
 
+  useEffect(() => {
+    // Follow the verification job for this task while verifying
+    if (!isVerifying || !taskId) return;
+    const unlisten = jobsApi.onJobUpdate((event) => {
+      if (event.job.task_id === parseInt(taskId)) {
+        setJobProgress(event);
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [isVerifying, taskId]);
+
   useEffect(() => {
     // Update duration every second when recording
     let interval: number | undefined;
@@ -75,22 +89,35 @@ export default function RecordingPage() {
       // Start AI verification
       setIsVerifying(true);
       try {
-        const result = await verificationApi.verify(parseInt(taskId!));
+        let outcome;
+        try {
+          outcome = await verificationApi.verify(parseInt(taskId!));
+        } catch (error) {
+          const verifyError = error as VerifyError;
+          if (verifyError?.kind !== 'budget_exceeded' || !window.confirm(`${verifyError.message}\n\nVerify anyway?`)) {
+            throw error;
+          }
+          outcome = await verificationApi.verify(parseInt(taskId!), true);
+        }
+        const { result, decision } = outcome;
         setIsVerifying(false);
         setVerificationComplete(true);
 
         // Show result
+        const summary = `Confidence: ${result.confidence}%\nTime on task: ${result.time_on_task_minutes.toFixed(1)} minutes\n\n${result.explanation}`;
         alert(
-          result.verified
-            ? `✅ Task Verified!\n\nConfidence: ${result.confidence}%\nTime on task: ${result.time_on_task_minutes.toFixed(1)} minutes\n\n${result.explanation}`
-            : `❌ Task Not Completed\n\nConfidence: ${result.confidence}%\nTime on task: ${result.time_on_task_minutes.toFixed(1)} minutes\n\n${result.explanation}\n\nIssues:\n${result.issues.join('\n')}`
+          decision.verdict === 'pass'
+            ? `✅ Task Verified!\n\n${summary}`
+            : decision.verdict === 'needs_review'
+            ? `⚠️ Needs Review\n\n${decision.reasons.join('\n')}\n\n${summary}`
+            : `❌ Task Not Completed\n\n${decision.reasons.join('\n')}\n\n${summary}\n\nIssues:\n${result.issues.join('\n')}`
         );
 
         // Navigate back to dashboard
         navigate('/');
       } catch (error) {
         setIsVerifying(false);
-        alert(`Verification failed: ${error}`);
+        alert(`Verification failed: ${(error as VerifyError)?.message ?? error}`);
         navigate('/');
       }
     } catch (error) {
@@ -113,8 +140,16 @@ export default function RecordingPage() {
       <div className="min-h-screen bg-gray-50 flex items-center justify-center">
         <div className="text-center">
           <div className="text-2xl font-bold text-gray-900 mb-4">Verifying with AI...</div>
-          <div className="text-gray-600 mb-4">Extracting frames and analyzing video</div>
+          <div className="text-gray-600 mb-4">{describeJobProgress(jobProgress)}</div>
           <div className="animate-spin rounded-full h-16 w-16 border-b-2 border-primary mx-auto"></div>
+          {jobProgress && (
+            <button
+              onClick={() => jobsApi.cancel(jobProgress.job.id)}
+              className="mt-6 px-4 py-2 text-sm text-gray-700 border border-gray-300 rounded hover:bg-gray-100"
+            >
+              Cancel verification
+            </button>
+          )}
         </div>
       </div>
     );
@@ -190,3 +225,24 @@ export default function RecordingPage() {
     </div>
   );
 }
+
+function describeJobProgress(progress: JobEvent | null): string {
+  if (!progress) return 'Extracting frames and analyzing video';
+
+  const segment = progress.window
+    ? ` (segment ${progress.window.index + 1} of ${progress.window.count})`
+    : '';
+
+  switch (progress.job.state) {
+    case 'queued':
+      return 'Waiting for other verifications to finish';
+    case 'extracting':
+      return `Extracting frames${segment}`;
+    case 'uploading':
+      return `Analyzing frames with AI${segment}`;
+    case 'parsing':
+      return `Processing results${segment}`;
+    default:
+      return 'Finishing up';
+  }
+}
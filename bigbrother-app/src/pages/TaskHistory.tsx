@@ -2,11 +2,16 @@ import { useEffect, useState } from 'react';
 import { useNavigate } from 'react-router-dom';
 import { invoke } from '@tauri-apps/api/core';
 import { useTaskStore } from '../store/taskStore';
-import type { Task } from '../lib/types';
+import type { Task, TimelineEntry } from '../lib/types';
 import { formatDateTime, formatDuration } from '../lib/utils';
 import { verificationApi } from '../lib/api';
 import ThemeToggle from '../components/ThemeToggle';
 
+// Timeline entries saved before offsets existed load with both offsets at 0 and the default category
+function isLegacyEntry(entry: TimelineEntry): boolean {
+  return !entry.start_seconds && !entry.end_seconds && (entry.category ?? 'on_task') === 'on_task';
+}
+
 export default function TaskHistory() {
   const navigate = useNavigate();
   const { tasks, fetchTasks } = useTaskStore();
@@ -18,7 +23,7 @@ export default function TaskHistory() {
   }, [fetchTasks]);
 
   const completedTasks = tasks
-    .filter(t => t.status === 'completed' || t.status === 'failed')
+    .filter(t => t.status === 'completed' || t.status === 'failed' || t.status === 'needs_review')
     .sort((a, b) => {
       const dateA = new Date(a.updated_at || a.created_at || 0).getTime();
       const dateB = new Date(b.updated_at || b.created_at || 0).getTime();
@@ -74,9 +79,11 @@ export default function TaskHistory() {
                       <h3 className="text-xl font-semibold text-gray-900 dark:text-white">{task.title}</h3>
                       <span className={`px-3 py-1 rounded-full text-sm font-medium ${task.status === 'completed'
                         ? 'bg-green-100 dark:bg-green-900 text-green-800 dark:text-green-200'
+                        : task.status === 'needs_review'
+                        ? 'bg-orange-100 dark:bg-orange-900 text-orange-800 dark:text-orange-200'
                         : 'bg-red-100 dark:bg-red-900 text-red-800 dark:text-red-200'
                         }`}>
-                        {task.status === 'completed' ? '✓ Verified' : '✗ Failed'}
+                        {task.status === 'completed' ? '✓ Verified' : task.status === 'needs_review' ? '? Needs Review' : '✗ Failed'}
                       </span>
                     </div>
                     {task.description && (
@@ -138,9 +145,13 @@ export default function TaskHistory() {
               <div className="space-y-3 mb-6">
                 <div className="flex justify-between text-sm">
                   <span className="text-gray-500 dark:text-gray-400">Status:</span>
-                  <span className={`font-medium ${selectedTask.status === 'completed' ? 'text-green-600 dark:text-green-400' : 'text-red-600 dark:text-red-400'
+                  <span className={`font-medium ${selectedTask.status === 'completed' ? 'text-green-600 dark:text-green-400'
+                    : selectedTask.status === 'needs_review' ? 'text-orange-600 dark:text-orange-400'
+                    : 'text-red-600 dark:text-red-400'
                     }`}>
-                    {selectedTask.status === 'completed' ? 'Verified Complete' : 'Failed Verification'}
+                    {selectedTask.status === 'completed' ? 'Verified Complete'
+                      : selectedTask.status === 'needs_review' ? 'Needs Review'
+                      : 'Failed Verification'}
                   </span>
                 </div>
                 <div className="flex justify-between text-sm">
@@ -210,10 +221,15 @@ export default function TaskHistory() {
                             <div>
                               <span className="text-gray-600 block mb-2">Activity Timeline:</span>
                               <div className="space-y-2 text-sm bg-gray-50 p-3 rounded max-h-48 overflow-y-auto">
-                                {report.timeline.map((entry: any, idx: number) => (
+                                {report.timeline.map((entry: TimelineEntry, idx: number) => (
                                   <div key={idx} className="flex gap-3">
-                                    <span className="font-mono text-gray-500">{entry.timestamp}</span>
+                                    {!isLegacyEntry(entry) && (
+                                      <span className="font-mono text-gray-500">
+                                        {formatDuration(Math.round(entry.start_seconds))}–{formatDuration(Math.round(entry.end_seconds))}
+                                      </span>
+                                    )}
                                     <span>{entry.activity}</span>
+                                    {entry.label && <span className="text-gray-500">({entry.label})</span>}
                                   </div>
                                 ))}
                               </div>
@@ -8,7 +8,7 @@ interface TaskStore {
   error: string | null;
   fetchTasks: () => Promise<void>;
   fetchPendingTasks: () => Promise<void>;
-  createTask: (title: string, description: string | null, dueDate: string, minDuration: number) => Promise<Task>;
+  createTask: (title: string, description: string | null, dueDate: string, minDuration: number, taskType?: string | null, criteria?: string[]) => Promise<Task>;
   updateTask: (id: number, task: Task) => Promise<void>;
   deleteTask: (id: number) => Promise<void>;
 }
@@ -38,10 +38,10 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
     }
   },
 
-  createTask: async (title, description, dueDate, minDuration) => {
+  createTask: async (title, description, dueDate, minDuration, taskType, criteria) => {
     set({ loading: true, error: null });
     try {
-      const task = await taskApi.create(title, description, dueDate, minDuration);
+      const task = await taskApi.create(title, description, dueDate, minDuration, taskType, criteria);
       set(state => ({ tasks: [...state.tasks, task], loading: false }));
       return task;
     } catch (error) {